@@ -0,0 +1,41 @@
+use crate::integrator::SURFACE_OFFSET;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
+use crate::surface::{RayIntersectionResult, Surface};
+use crate::vector::Vector;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AmbientOcclusion {
+    pub sample_count: usize,
+    pub max_distance: f64
+}
+
+impl AmbientOcclusion {
+    pub fn from(sample_count: usize, max_distance: f64) -> Option<AmbientOcclusion> {
+        if sample_count == 0 || max_distance <= 0f64 {
+            return None;
+        }
+
+        Some(AmbientOcclusion { sample_count, max_distance })
+    }
+
+    // rays are cosine weighted, so the fraction is already weighted by how much each direction lights the point
+    pub fn get_unoccluded_fraction(&self, ray: Ray, point: Vector, normal: Vector, surface: &dyn Surface, random: &mut Random) -> f64 {
+        let unoccluded_count = (0..self.sample_count)
+            .filter(|_| {
+                let occlusion_ray = match ray.spawn(point + normal * SURFACE_OFFSET, sampling::cosine_weighted_hemisphere(normal, random)) {
+                    Some(occlusion_ray) => occlusion_ray,
+                    None => return true
+                };
+
+                match surface.intersect_with_ray(occlusion_ray) {
+                    RayIntersectionResult::Intersected { t, .. } => t >= self.max_distance,
+                    RayIntersectionResult::NoIntersection => true
+                }
+            })
+            .count();
+
+        unoccluded_count as f64 / self.sample_count as f64
+    }
+}
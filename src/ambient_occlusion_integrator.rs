@@ -0,0 +1,91 @@
+use crate::ambient_occlusion::AmbientOcclusion;
+use crate::color::Color;
+use crate::integrator::Integrator;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::surface::RayIntersectionResult;
+
+pub struct AmbientOcclusionIntegrator {
+    ambient_occlusion: AmbientOcclusion
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn from(ambient_occlusion: AmbientOcclusion) -> AmbientOcclusionIntegrator {
+        AmbientOcclusionIntegrator { ambient_occlusion }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, random: &mut Random) -> Color {
+        let (surface_normal, t) = match scene.surface.intersect_with_ray(ray) {
+            RayIntersectionResult::Intersected { surface_normal, t, .. } => (surface_normal, t),
+            RayIntersectionResult::NoIntersection => return Color::WHITE
+        };
+
+        let normal = if surface_normal.dot(ray.direction) > 0f64 { -surface_normal } else { surface_normal };
+        let unoccluded_fraction = self.ambient_occlusion.get_unoccluded_fraction(ray, ray.point_at(t), normal, scene.surface, random);
+
+        Color::from(unoccluded_fraction, unoccluded_fraction, unoccluded_fraction, 1f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infinite_plane::InfinitePlane;
+    use crate::material::Material;
+    use crate::multisurface::MultiSurface;
+    use crate::sphere::Sphere;
+    use crate::surface::Surface;
+    use crate::vector::Vector;
+
+    fn white_material() -> Material {
+        Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)
+    }
+
+    fn radiance_of(surface: &dyn Surface, max_distance: f64, ray: Ray) -> Color {
+        let integrator = AmbientOcclusionIntegrator::from(AmbientOcclusion::from(64, max_distance).unwrap());
+        integrator.get_radiance(ray, &Scene::from(surface, vec![]), &mut Random::from_seed(1))
+    }
+
+    fn downwards_ray() -> Ray {
+        Ray::from(Vector::from(0f64, 0.5f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap()
+    }
+
+    #[test]
+    fn open_floor_is_not_occluded() {
+        let floor = InfinitePlane::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), white_material()).unwrap();
+
+        assert!(radiance_of(&floor, 10f64, downwards_ray()) == Color::WHITE);
+    }
+
+    #[test]
+    fn floor_under_a_ceiling_is_fully_occluded() {
+        let floor = InfinitePlane::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), white_material()).unwrap();
+        let ceiling = InfinitePlane::from(Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, -1f64, 0f64), white_material()).unwrap();
+        let world = MultiSurface::from(vec![&floor, &ceiling]);
+
+        // grazing rays reach the ceiling far away, so only an unlimited distance sees it everywhere
+        assert!(radiance_of(&world, f64::INFINITY, downwards_ray()) == Color::BLACK);
+
+        // occluders farther away than the maximum distance do not count
+        assert!(radiance_of(&world, 0.5f64, downwards_ray()) == Color::WHITE);
+    }
+
+    #[test]
+    fn inside_of_a_sphere_is_fully_occluded() {
+        // the ray hits the back face, the normal is turned towards the inside before sampling
+        let sphere = Sphere::from(Vector::ZERO, 2f64, white_material()).unwrap();
+        let ray = Ray::from(Vector::ZERO, Vector::from(1f64, 1f64, 0f64)).unwrap();
+
+        assert!(radiance_of(&sphere, 10f64, ray) == Color::BLACK);
+    }
+
+    #[test]
+    fn missed_rays_see_open_sky() {
+        let sphere = Sphere::from(Vector::from(0f64, 0f64, 10f64), 1f64, white_material()).unwrap();
+
+        assert!(radiance_of(&sphere, 10f64, downwards_ray()) == Color::WHITE);
+    }
+}
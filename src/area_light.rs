@@ -0,0 +1,316 @@
+use std::f64::consts::PI;
+
+use crate::basis::Basis;
+use crate::color::Color;
+use crate::integrator::SURFACE_OFFSET;
+use crate::light_source::{LightSample, LightSource};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
+use crate::vector::Vector;
+
+const CONTAINS_TOLERANCE: f64 = 1e-6;
+
+pub enum AreaLightShape {
+    Rectangle { corner: Vector, edge_u: Vector, edge_v: Vector },
+    Disk { center: Vector, basis: Basis, radius: f64 },
+    Sphere { center: Vector, radius: f64 }
+}
+
+impl AreaLightShape {
+    pub fn rectangle(corner: Vector, edge_u: Vector, edge_v: Vector) -> Option<AreaLightShape> {
+        if edge_u.cross(edge_v).is_zero_approx() {
+            return None;
+        }
+
+        Some(AreaLightShape::Rectangle { corner, edge_u, edge_v })
+    }
+
+    pub fn disk(center: Vector, normal: Vector, radius: f64) -> Option<AreaLightShape> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Basis::from_w(normal).map(|basis| AreaLightShape::Disk { center, basis, radius })
+    }
+
+    pub fn sphere(center: Vector, radius: f64) -> Option<AreaLightShape> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Some(AreaLightShape::Sphere { center, radius })
+    }
+
+    pub fn center(&self) -> Vector {
+        match self {
+            AreaLightShape::Rectangle { corner, edge_u, edge_v } => *corner + (*edge_u + *edge_v) * 0.5f64,
+            AreaLightShape::Disk { center, .. } => *center,
+            AreaLightShape::Sphere { center, .. } => *center
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        match self {
+            AreaLightShape::Rectangle { edge_u, edge_v, .. } => edge_u.cross(*edge_v).length(),
+            AreaLightShape::Disk { radius, .. } => PI * radius * radius,
+            AreaLightShape::Sphere { radius, .. } => 4f64 * PI * radius * radius
+        }
+    }
+
+    pub fn bounds(&self) -> (Vector, Vector) {
+        match self {
+            AreaLightShape::Rectangle { corner, edge_u, edge_v } => {
+                let corners = [*corner, *corner + *edge_u, *corner + *edge_v, *corner + *edge_u + *edge_v];
+                let minimum = corners.iter().fold(*corner, |minimum, point| Vector::from(minimum.x.min(point.x), minimum.y.min(point.y), minimum.z.min(point.z)));
+                let maximum = corners.iter().fold(*corner, |maximum, point| Vector::from(maximum.x.max(point.x), maximum.y.max(point.y), maximum.z.max(point.z)));
+                (minimum, maximum)
+            },
+            AreaLightShape::Disk { center, radius, .. } => (*center - Vector::from(*radius, *radius, *radius), *center + Vector::from(*radius, *radius, *radius)),
+            AreaLightShape::Sphere { center, radius } => (*center - Vector::from(*radius, *radius, *radius), *center + Vector::from(*radius, *radius, *radius))
+        }
+    }
+
+    // whether the point lies on the emitting surface, up to a tolerance that scales with the shape
+    pub fn contains(&self, point: Vector) -> bool {
+        match self {
+            AreaLightShape::Rectangle { corner, edge_u, edge_v } => {
+                let offset = point - *corner;
+                let (u, v) = (offset.dot(*edge_u) / edge_u.length_squared(), offset.dot(*edge_v) / edge_v.length_squared());
+                let height = offset.dot(edge_u.cross(*edge_v).normalized_or_zero());
+
+                height.abs() <= CONTAINS_TOLERANCE * edge_u.length().max(edge_v.length()).max(1f64) && (0f64..=1f64).contains(&u) && (0f64..=1f64).contains(&v)
+            },
+            AreaLightShape::Disk { center, basis, radius } => {
+                let offset = point - *center;
+                offset.dot(basis.w).abs() <= CONTAINS_TOLERANCE * radius.max(1f64) && offset.length() <= *radius * (1f64 + CONTAINS_TOLERANCE)
+            },
+            AreaLightShape::Sphere { center, radius } => ((point - *center).length() - *radius).abs() <= CONTAINS_TOLERANCE * radius.max(1f64)
+        }
+    }
+
+    // returns the sampled point, its normal and the area the point was picked from
+    pub fn sample_point(&self, seen_from: Vector, random: &mut Random) -> (Vector, Vector, f64) {
+        match self {
+            AreaLightShape::Sphere { center, radius } => {
+                // only the half facing the point can be seen from it
+                let normal = sampling::uniform_hemisphere((seen_from - *center).normalized_or_zero(), random);
+                (*center + normal * *radius, normal, self.area() * 0.5f64)
+            },
+            _ => {
+                let (point, normal) = self.sample_surface(random);
+                (point, normal, self.area())
+            }
+        }
+    }
+
+    // picks a point uniformly over the whole emitting surface
+    pub fn sample_surface(&self, random: &mut Random) -> (Vector, Vector) {
+        match self {
+            AreaLightShape::Rectangle { corner, edge_u, edge_v } => (*corner + *edge_u * random.next_f64() + *edge_v * random.next_f64(), edge_u.cross(*edge_v).normalized_or_zero()),
+            AreaLightShape::Disk { center, basis, radius } => {
+                let (disk_x, disk_y) = sampling::concentric_disk(random);
+                (*center + basis.to_world(Vector::from(disk_x, disk_y, 0f64)) * *radius, basis.w)
+            },
+            AreaLightShape::Sphere { center, radius } => {
+                let normal = sampling::uniform_sphere(random);
+                (*center + normal * *radius, normal)
+            }
+        }
+    }
+}
+
+pub struct AreaLight {
+    pub shape: AreaLightShape,
+    pub intensity: f64,
+    pub color: Color,
+    pub shadow_sample_count: usize
+}
+
+impl AreaLight {
+    pub fn from(shape: AreaLightShape, intensity: f64, color: Color, shadow_sample_count: usize) -> Option<AreaLight> {
+        if intensity < 0f64 || shadow_sample_count == 0 {
+            return None;
+        }
+
+        Some(AreaLight { shape, intensity, color, shadow_sample_count })
+    }
+
+    // converts the radiance leaving a patch of the light into the irradiance it gives the point
+    fn get_received_color(&self, point: Vector, light_point: Vector, light_normal: Vector, area: f64) -> Color {
+        let to_point = point - light_point;
+        let squared_distance = to_point.length_squared();
+
+        if squared_distance <= 0f64 {
+            return Color::ZERO;
+        }
+
+        let cosine_at_light = light_normal.dot(to_point) / squared_distance.sqrt();
+
+        if cosine_at_light <= 0f64 {
+            return Color::ZERO;
+        }
+
+        self.color * (self.intensity * cosine_at_light * area / squared_distance)
+    }
+}
+
+impl LightSource for AreaLight {
+    fn get_light_direction_from(&self, point: Vector) -> Vector {
+        (point - self.shape.center()).normalized_or_zero()
+    }
+
+    fn get_light_distance_from(&self, point: Vector) -> f64 {
+        (point - self.shape.center()).length()
+    }
+
+    fn get_light_color(&self, point: Vector) -> Color {
+        let center = self.shape.center();
+
+        match self.shape {
+            AreaLightShape::Rectangle { edge_u, edge_v, .. } => self.get_received_color(point, center, edge_u.cross(edge_v).normalized_or_zero(), self.shape.area()),
+            AreaLightShape::Disk { basis, .. } => self.get_received_color(point, center, basis.w, self.shape.area()),
+            AreaLightShape::Sphere { radius, .. } => self.get_received_color(point, center, (point - center).normalized_or_zero(), PI * radius * radius)
+        }
+    }
+
+    fn get_power(&self) -> f64 {
+        PI * self.shape.area() * self.intensity * self.color.luminance()
+    }
+
+    fn get_bounds(&self) -> Option<(Vector, Vector)> {
+        Some(self.shape.bounds())
+    }
+
+    fn get_shadow_sample_count(&self) -> usize {
+        self.shadow_sample_count
+    }
+
+    fn covers_point(&self, point: Vector) -> bool {
+        self.shape.contains(point)
+    }
+
+    // cosine weighted directions cancel the emitter's cosine, which leaves radiance times area times pi
+    fn emit_photon(&self, _scene_center: Vector, _scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+        let (point, normal) = self.shape.sample_surface(random);
+        let direction = sampling::cosine_weighted_hemisphere(normal, random);
+
+        Ray::from(point + normal * SURFACE_OFFSET, direction).map(|ray| (ray, self.color * (self.intensity * self.shape.area() * PI)))
+    }
+
+    fn sample_from(&self, point: Vector, random: &mut Random) -> LightSample {
+        let (light_point, light_normal, area) = self.shape.sample_point(point, random);
+
+        LightSample {
+            direction: (point - light_point).normalized_or_zero(),
+            distance: (point - light_point).length(),
+            color: self.get_received_color(point, light_point, light_normal, area)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+    use crate::scene::Scene;
+    use crate::sphere::Sphere;
+
+    const SAMPLE_COUNT: usize = 100000;
+
+    fn mean_sample_color(light: &AreaLight, point: Vector) -> f64 {
+        let mut random = Random::from_seed(1);
+        (0..SAMPLE_COUNT).map(|_| light.sample_from(point, &mut random).color.red).sum::<f64>() / SAMPLE_COUNT as f64
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.01f64 * expected, "{} against {}", actual, expected);
+    }
+
+    // samples are weighted by the radiance times the cosine at the light over the squared distance, so they add up to the
+    // radiance times the solid angle the light covers, projected onto the light's own surface
+    #[test]
+    fn disk_light_samples_are_unbiased() {
+        let light = AreaLight::from(AreaLightShape::disk(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), 1.5f64).unwrap(), 2f64, Color::WHITE, 1).unwrap();
+        let height = 2f64;
+
+        assert_close(mean_sample_color(&light, Vector::from(0f64, height, 0f64)), 2f64 * 2f64 * PI * (1f64 - height / (height * height + 1.5f64 * 1.5f64).sqrt()));
+    }
+
+    #[test]
+    fn sphere_light_samples_are_unbiased() {
+        let light = AreaLight::from(AreaLightShape::sphere(Vector::ZERO, 1f64).unwrap(), 2f64, Color::WHITE, 1).unwrap();
+        let distance = 3f64;
+
+        // the visible cap of a sphere covers 2 pi (1 - cos) steradians, with sin equal to the radius over the distance
+        assert_close(mean_sample_color(&light, Vector::from(0f64, 0f64, distance)), 2f64 * 2f64 * PI * (1f64 - (1f64 - 1f64 / (distance * distance)).sqrt()));
+    }
+
+    #[test]
+    fn rectangle_light_samples_are_unbiased() {
+        let shape = AreaLightShape::rectangle(Vector::from(-1f64, 0f64, -0.5f64), Vector::from(0f64, 0f64, 1f64), Vector::from(2f64, 0f64, 0f64)).unwrap();
+        let light = AreaLight::from(shape, 2f64, Color::WHITE, 1).unwrap();
+        let point = Vector::from(0.5f64, 1f64, 0.2f64);
+
+        // the same integral by the midpoint rule over a fine grid of patches
+        let steps = 400;
+        let patch_area = 2f64 / (steps * steps) as f64;
+        let expected: f64 = (0..steps * steps)
+            .map(|index| {
+                let light_point = Vector::from(-1f64 + 2f64 * ((index % steps) as f64 + 0.5f64) / steps as f64, 0f64, -0.5f64 + ((index / steps) as f64 + 0.5f64) / steps as f64);
+                let to_point = point - light_point;
+                2f64 * to_point.y / to_point.length() * patch_area / to_point.length_squared()
+            })
+            .sum();
+
+        assert_close(mean_sample_color(&light, point), expected);
+    }
+
+    #[test]
+    fn sampled_points_lie_on_the_light() {
+        let shapes = [
+            AreaLightShape::rectangle(Vector::from(1f64, 2f64, 3f64), Vector::from(1f64, 0f64, 0f64), Vector::from(0f64, 0f64, 2f64)).unwrap(),
+            AreaLightShape::disk(Vector::from(1f64, 2f64, 3f64), Vector::from(1f64, 1f64, 0f64), 0.5f64).unwrap(),
+            AreaLightShape::sphere(Vector::from(1f64, 2f64, 3f64), 0.5f64).unwrap()
+        ];
+        let seen_from = Vector::from(1f64, 10f64, 3f64);
+        let mut random = Random::from_seed(2);
+
+        for shape in shapes {
+            let expected_area = match shape {
+                AreaLightShape::Sphere { .. } => shape.area() * 0.5f64,
+                _ => shape.area()
+            };
+
+            for _ in 0..1000 {
+                let (point, normal, area) = shape.sample_point(seen_from, &mut random);
+
+                assert!(shape.contains(point));
+                assert!((normal.length() - 1f64).abs() < 1e-9);
+                assert_eq!(area, expected_area);
+
+                // a sphere is only sampled on the half that faces the point
+                if let AreaLightShape::Sphere { center, .. } = shape {
+                    assert!((point - center).dot(seen_from - center) >= 0f64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn partly_covered_area_light_casts_a_soft_shadow() {
+        let light = AreaLight::from(AreaLightShape::disk(Vector::from(0f64, 4f64, 0f64), Vector::from(0f64, -1f64, 0f64), 1f64).unwrap(), 1f64, Color::WHITE, 1).unwrap();
+        let blocker = Sphere::from(Vector::from(-1f64, 2f64, 0f64), 0.8f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let scene = Scene::from(&blocker, vec![&light]);
+        let mut random = Random::from_seed(3);
+
+        let unoccluded_count = (0..1000)
+            .filter(|_| {
+                let sample = light.sample_from(Vector::ZERO, &mut random);
+                scene.is_unoccluded(Ray::from(Vector::ZERO, -sample.direction).unwrap(), sample.distance)
+            })
+            .count();
+
+        assert!((100..900).contains(&unoccluded_count), "{} of 1000 samples reach the light", unoccluded_count);
+    }
+}
@@ -21,4 +21,13 @@ impl Basis {
             u, v, w
         })
     }
-}
\ No newline at end of file
+
+    pub fn from_w(w: Vector) -> Option<Basis> {
+        let helper = if w.normalized_or_zero().x.abs() > 0.9f64 { Vector::from(0f64, 1f64, 0f64) } else { Vector::from(1f64, 0f64, 0f64) };
+        Basis::from_wv(w, helper)
+    }
+
+    pub fn to_world(&self, local: Vector) -> Vector {
+        self.u * local.x + self.v * local.y + self.w * local.z
+    }
+}
@@ -1,99 +1,314 @@
-
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
 
 use crate::basis::Basis;
 use crate::color::Color;
-use crate::light_ray::LightRay;
-use crate::light_source::LightSource;
-use crate::surface::{RayIntersectionResult, Surface};
+use crate::film::{Film, FilmTile};
+use crate::perspective_projection::PerspectiveProjection;
+use crate::projection::Projection;
+use crate::random::Random;
 use crate::ray::Ray;
-use crate::screen::{Screen};
+use crate::render_settings::{AdaptiveSampling, ProgressiveSettings, RenderSettings};
+use crate::sample_pattern::SamplePattern;
+use crate::scene::Scene;
+use crate::screen::{Screen, ScreenTiles, Tile};
 
 use crate::vector::Vector;
 
+#[derive(Copy, Clone)]
+struct RenderPass {
+    index: u64,
+    samples_per_pixel: usize,
+    sample_pattern: SamplePattern,
+    adaptive_sampling: Option<AdaptiveSampling>
+}
+
+#[derive(Clone)]
 pub struct Camera {
     pub basis: Basis,
     pub position: Vector,
-    pub focal_length: f64,
-    pub sensor_extents: Vector
+    pub projection: Arc<dyn Projection>,
+    pub shutter_open: f64,
+    pub shutter_close: f64
 }
 
 impl Camera {
     pub fn from(position: Vector, up_direction: Vector, look_direction: Vector, focal_length: f64, sensor_size: Vector) -> Option<Camera> {
+        let projection = PerspectiveProjection::from(focal_length, sensor_size)?;
+        Camera::with_projection(position, up_direction, look_direction, Arc::new(projection))
+    }
+
+    pub fn with_projection(position: Vector, up_direction: Vector, look_direction: Vector, projection: Arc<dyn Projection>) -> Option<Camera> {
         let basis = match Basis::from_wv(-look_direction, up_direction) {
             Some(b) => b,
             _ => return None
         };
 
-        let sensor_extents = sensor_size / 2f64;
-
         Some(Camera {
             basis,
             position,
-            focal_length,
-            sensor_extents
+            projection,
+            shutter_open: 0f64,
+            shutter_close: 0f64
         })
     }
 
-    pub fn print_to_screen<S: Surface, L: LightSource>(&self, surface: &S, light_source: &L, screen: &mut Screen) {
+    pub fn print_to_screen(&self, scene: &Scene, screen: &mut Screen, settings: &RenderSettings) {
+        let (width, height) = screen.resolution();
+        let mut film = Film::from(width, height);
+
+        self.render_to_film(scene, &mut film, settings);
+        film.develop_into(screen);
+    }
+
+    pub fn print_progressively(&self, scene: &Scene, screen: &mut Screen, settings: &RenderSettings, progressive_settings: &ProgressiveSettings) -> io::Result<()> {
         let (width, height) = screen.resolution();
-        let pixel_extents = Vector::from(self.sensor_extents.x / width as f64, self.sensor_extents.y / height as f64, 0f64);
+        let mut film = Film::from(width, height);
+        let mut last_write = Instant::now();
+
+        for pass_index in 0..progressive_settings.pass_count {
+            // the first pass is a single centered sample per pixel so a preview shows up quickly,
+            // refinement passes keep adding samples where each pixel is still noisy
+            let pass = if pass_index == 0 {
+                RenderPass { index: 0, samples_per_pixel: 1, sample_pattern: SamplePattern::Grid, adaptive_sampling: None }
+            } else {
+                RenderPass { index: pass_index as u64, samples_per_pixel: settings.samples_per_pixel, sample_pattern: settings.sample_pattern.refinement(), adaptive_sampling: settings.adaptive_sampling }
+            };
 
-        for (px, py) in screen.pixel_positions() {
-            let pixel_position: Vector = self.pixel_world_position_at(px, py, width, height, pixel_extents);
-            let ray: Ray = Ray::from_points(self.position, pixel_position).unwrap();
+            // the timed writes are checked after every merged tile, so a long pass cannot hold them back
+            self.render_pass(scene, &mut film, settings, pass, &mut |film| {
+                match progressive_settings.write_every {
+                    Some(interval) if last_write.elapsed() >= interval => {
+                        last_write = Instant::now();
+                        film.develop_into(screen);
+                        screen.write_pbm(&progressive_settings.output_path)
+                    },
+                    _ => Ok(())
+                }
+            })?;
 
-            screen.paint_at(px, py, self.get_final_color_from_ray(ray, surface, light_source, 3)).expect("should not go wrong");
+            let finished_passes = pass_index + 1;
+            let is_write_due = pass_index == 0
+                || finished_passes == progressive_settings.pass_count
+                || progressive_settings.write_every_passes.is_some_and(|passes| finished_passes % passes == 0);
+
+            if is_write_due {
+                film.develop_into(screen);
+                screen.write_pbm(&progressive_settings.output_path)?;
+                last_write = Instant::now();
+            }
         }
+
+        Ok(())
     }
 
-    pub fn pixel_world_position_at(&self, x: usize, y: usize, width: usize, height: usize, pixel_extents: Vector) -> Vector {
-        self.position - self.basis.u * pixel_extents.x * (2f64 * x as f64 - width as f64 + 1f64) - self.basis.v * pixel_extents.y * (2f64 * y as f64 - height as f64 + 1f64) - self.basis.w * self.focal_length
+    pub fn render_to_film(&self, scene: &Scene, film: &mut Film, settings: &RenderSettings) {
+        let pass = RenderPass { index: 0, samples_per_pixel: settings.samples_per_pixel, sample_pattern: settings.sample_pattern, adaptive_sampling: settings.adaptive_sampling };
+        self.render_pass(scene, film, settings, pass, &mut |_| Ok(())).expect("should not go wrong");
     }
 
-    fn get_final_color_from_ray<S: Surface, L: LightSource>(&self, ray: Ray, surface: &S, light_source: &L, reflections: u8) -> Color {
-        match self.get_final_ray(ray, surface, light_source, reflections) {
-            Some(ray) => ray.color,
-            _ => Color::BLACK
-        }
+    fn render_pass(&self, scene: &Scene, film: &mut Film, settings: &RenderSettings, pass: RenderPass, on_tile_merged: &mut dyn FnMut(&Film) -> io::Result<()>) -> io::Result<()> {
+        let (width, height) = film.resolution();
+
+        // adaptive sampling keeps estimating each pixel's noise from every sample it got in earlier passes
+        let previous_statistics = if pass.adaptive_sampling.is_some() { film.luminance_statistics() } else { Vec::new() };
+
+        let tiles: Vec<Tile> = ScreenTiles::from(width, height, settings.tile_size).collect();
+        let next_tile = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel::<(usize, FilmTile)>();
+
+        thread::scope(|scope| {
+            for _ in 0..settings.thread_count.clamp(1, tiles.len().max(1)) {
+                let sender = sender.clone();
+                let tiles = &tiles;
+                let next_tile = &next_tile;
+                let previous_statistics = &previous_statistics;
+
+                scope.spawn(move || {
+                    loop {
+                        let tile_index = next_tile.fetch_add(1, Ordering::Relaxed);
+                        let tile = match tiles.get(tile_index) {
+                            Some(tile) => *tile,
+                            None => break
+                        };
+
+                        let mut film_tile = FilmTile::from(tile, width, height, settings.filter.as_ref());
+
+                        for (px, py) in tile.pixel_positions() {
+                            let previous = previous_statistics.get(width * py + px).copied().unwrap_or((0, 0f64, 0f64));
+                            self.sample_pixel(px, py, width, height, scene, settings, pass, previous, &mut film_tile);
+                        }
+
+                        if sender.send((tile_index, film_tile)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+
+            drop(sender);
+
+            // tiles overlap once the filter spreads samples into neighbouring pixels,
+            // so they are merged in a fixed order to keep the output deterministic
+            let mut pending_tiles = BTreeMap::new();
+            let mut next_merge = 0;
+            let mut result = Ok(());
+
+            for (tile_index, film_tile) in receiver {
+                pending_tiles.insert(tile_index, film_tile);
+
+                while let Some(film_tile) = pending_tiles.remove(&next_merge) {
+                    film.merge_tile(&film_tile);
+                    next_merge += 1;
+
+                    if result.is_ok() {
+                        result = on_tile_merged(film);
+                    }
+                }
+            }
+
+            result
+        })
     }
 
-    fn get_final_ray<S: Surface, L: LightSource>(&self, ray: Ray, surface: &S, light_source: &L, reflections: u8) -> Option<LightRay> {
-        if reflections == 0 {
-            return None;
-        }
+    #[allow(clippy::too_many_arguments)]
+    fn sample_pixel(&self, px: usize, py: usize, width: usize, height: usize, scene: &Scene, settings: &RenderSettings, pass: RenderPass, previous: (usize, f64, f64), film_tile: &mut FilmTile) {
+        let mut random = Random::from_pixel(px, py, pass.index);
+        let max_samples = match pass.adaptive_sampling {
+            Some(adaptive_sampling) => adaptive_sampling.max_samples_per_pixel.max(pass.samples_per_pixel),
+            None => pass.samples_per_pixel
+        };
+
+        let mut sample_pattern = pass.sample_pattern;
+        let (mut sample_count, mut luminance_sum, mut luminance_squared_sum) = (0usize, 0f64, 0f64);
+        let (previous_count, previous_luminance_sum, previous_luminance_squared_sum) = previous;
+
+        while sample_count < max_samples {
+            // pixels that are already quiet enough, possibly from earlier passes alone, get no more samples
+            if let Some(adaptive_sampling) = pass.adaptive_sampling {
+                let relative_error = AdaptiveSampling::relative_error(previous_luminance_sum + luminance_sum, previous_luminance_squared_sum + luminance_squared_sum, previous_count + sample_count);
 
-        let ray_intersection = surface.intersect_with_ray(ray);
+                if relative_error <= adaptive_sampling.error_threshold {
+                    break;
+                }
+            }
 
-        match ray_intersection {
-            RayIntersectionResult::Intersected { surface_material, surface_normal, t } => {
-                let reflection_point = ray.point_at(t);
-                let direction_from_camera = (reflection_point - self.position).normalized_or_zero();
-                let reflection_ray = Ray::from(reflection_point, direction_from_camera.reflected(surface_normal)).unwrap();
+            let batch_size = pass.samples_per_pixel.min(max_samples - sample_count);
 
-                let ray_to_light = Ray::from(reflection_point, -light_source.get_light_direction_from(reflection_point)).unwrap();
-                let light_multiplier = match surface.intersect_with_ray(ray_to_light) {
-                    RayIntersectionResult::NoIntersection => 1f64,
-                    _ => 0f64
+            for (offset_x, offset_y) in sample_pattern.pixel_offsets(batch_size, &mut random) {
+                let (film_x, film_y) = (px as f64 + offset_x, py as f64 + offset_y);
+                let color = match self.generate_ray(film_x, film_y, width, height, &mut random) {
+                    Some(ray) => settings.integrator.get_radiance(ray, scene, &mut random),
+                    None => Color::BLACK
                 };
 
-                let diffuse_multiplier = surface_material.diffuse_coefficient * surface_normal.dot(-light_source.get_light_direction_from(reflection_point)).max(0f64);
+                film_tile.add_sample(film_x, film_y, color, settings.filter.as_ref());
 
-                let bisector = (-direction_from_camera + ray_to_light.direction).normalized_or_zero();
+                luminance_sum += color.luminance();
+                luminance_squared_sum += color.luminance().powi(2);
+            }
 
-                let specular_multiplier = surface_material.specular_coefficient * bisector.dot(surface_normal).max(0f64).powf(surface_material.shininess_coefficient);
+            sample_count += batch_size;
+            sample_pattern = sample_pattern.refinement();
+        }
+    }
 
-                let received_color = light_source.get_light_color() * light_multiplier;
-                let reflection_color = match self.get_final_ray(reflection_ray, surface, light_source, reflections - 1) {
-                    Some(light_ray) => light_ray.color * surface_material.mirror_reflection_coefficient,
-                    _ => Color::ZERO
-                };
+    pub fn generate_ray(&self, film_x: f64, film_y: f64, width: usize, height: usize, random: &mut Random) -> Option<Ray> {
+        let screen_x = 2f64 * film_x / width as f64 - 1f64;
+        let screen_y = 1f64 - 2f64 * film_y / height as f64;
 
-                LightRay::from(reflection_point, -ray.direction, Color::mix_of(vec![surface_material.color, (received_color + reflection_color) * (diffuse_multiplier + specular_multiplier)]))
-            },
+        let ray = self.projection.generate_ray(&self.basis, self.position, screen_x, screen_y, width as f64 / height as f64, random)?;
 
-            _ => None
+        if self.shutter_close > self.shutter_open {
+            Some(ray.at_time(self.shutter_open + (self.shutter_close - self.shutter_open) * random.next_f64()))
+        } else {
+            Some(ray.at_time(self.shutter_open))
         }
     }
-    //fn get_materials_from_propagation<S: Surface, L: LightSource>(ray: Ray, surface: &S, light_source: &L, reflections: u8) -> Vec<(>
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directional_light::DirectionalLight;
+    use crate::filter::{BoxFilter, MitchellFilter};
+    use crate::infinite_plane::InfinitePlane;
+    use crate::material::Material;
+    use crate::multisurface::MultiSurface;
+    use crate::path_tracing_integrator::PathTracingIntegrator;
+    use crate::sphere::Sphere;
+    use crate::whitted_integrator::WhittedIntegrator;
+
+    fn render(thread_count: usize) -> Screen {
+        let sphere = Sphere::from(Vector::from(0f64, 0f64, 6f64), 2f64, Material::from(Color::from(1f64, 0.4f64, 0.2f64, 1f64), 1f64, 0.5f64, 16f64, 0.3f64)).unwrap();
+        let floor = InfinitePlane::from(Vector::from(0f64, -2f64, 0f64), Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let surface = MultiSurface::from(vec![&sphere, &floor]);
+        let light = DirectionalLight::from(Vector::from(1f64, -1f64, 1f64), Color::WHITE).unwrap();
+        let scene = Scene::from(&surface, vec![&light]);
+
+        let camera = Camera::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, 0f64, 1f64), 8f64, Vector::from(16f64, 9f64, 0f64)).unwrap();
+
+        // a wide filter makes neighbouring tiles write into the same pixels
+        let filter = Box::new(MitchellFilter::from(2f64, 1f64 / 3f64, 1f64 / 3f64).unwrap());
+        let settings = RenderSettings::from(thread_count, 4, 4, SamplePattern::Jittered, filter, Box::new(PathTracingIntegrator::from(3))).unwrap();
+
+        let mut screen = Screen::from(23, 13, Color::BLACK);
+        camera.print_to_screen(&scene, &mut screen, &settings);
+        screen
+    }
+
+    #[test]
+    fn adaptive_sampling_spends_more_samples_on_noisy_pixels() {
+        let sphere = Sphere::from(Vector::from(0f64, 0f64, 6f64), 2f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let light = DirectionalLight::from(Vector::from(0f64, 0f64, 1f64), Color::WHITE).unwrap();
+        let scene = Scene::from(&sphere, vec![&light]);
+        let camera = Camera::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, 0f64, 1f64), 8f64, Vector::from(16f64, 9f64, 0f64)).unwrap();
+
+        let mut settings = RenderSettings::from(2, 4, 2, SamplePattern::Jittered, Box::new(BoxFilter::from(0.5f64).unwrap()), Box::new(WhittedIntegrator::from(1))).unwrap();
+        settings.adaptive_sampling = AdaptiveSampling::from(32, 0.01f64);
+
+        let mut film = Film::from(32, 18);
+        camera.render_to_film(&scene, &mut film, &settings);
+        let sample_counts: Vec<usize> = film.luminance_statistics().iter().map(|(sample_count, _, _)| *sample_count).collect();
+
+        // the black background settles after the first batch, pixels on the sphere's outline keep sampling up to the limit
+        assert_eq!(sample_counts[0], 2);
+        assert!(sample_counts.iter().all(|sample_count| (2..=32).contains(sample_count)));
+        assert!(sample_counts.contains(&32));
+    }
+
+    #[test]
+    fn adaptive_sampling_remembers_pixels_that_converged_in_earlier_passes() {
+        let empty = MultiSurface::from(Vec::new());
+        let scene = Scene::from(&empty, Vec::new());
+        let camera = Camera::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, 0f64, 1f64), 8f64, Vector::from(16f64, 9f64, 0f64)).unwrap();
+
+        let mut settings = RenderSettings::from(2, 4, 2, SamplePattern::Jittered, Box::new(BoxFilter::from(0.5f64).unwrap()), Box::new(PathTracingIntegrator::from(1))).unwrap();
+        settings.adaptive_sampling = AdaptiveSampling::from(16, 0.01f64);
+
+        let mut film = Film::from(5, 3);
+        camera.render_to_film(&scene, &mut film, &settings);
+        camera.render_to_film(&scene, &mut film, &settings);
+
+        // the black background has no noise, so the first batch already settles every pixel for good
+        assert!(film.luminance_statistics().iter().all(|(sample_count, _, _)| *sample_count == 2));
+    }
+
+    #[test]
+    fn multithreaded_render_matches_the_single_threaded_one() {
+        let single_threaded = render(1);
+
+        for thread_count in [2, 4, 7] {
+            let multithreaded = render(thread_count);
+
+            for (x, y) in single_threaded.pixel_positions() {
+                let (expected, actual) = (single_threaded.get_color_at(x, y).unwrap(), multithreaded.get_color_at(x, y).unwrap());
+                assert_eq!((actual.red, actual.green, actual.blue), (expected.red, expected.green, expected.blue), "pixel {} {} with {} threads", x, y, thread_count);
+            }
+        }
+    }
+}
@@ -34,6 +34,14 @@ impl Color {
             .fold(Color::WHITE, |color1, color2| Color::from(color1.red * color2.red, color1.green * color2.green, color1.blue * color2.blue, color1.alpha * color2.alpha))
     }
 
+    pub fn max_component(&self) -> f64 {
+        self.red.max(self.green).max(self.blue)
+    }
+
+    pub fn luminance(&self) -> f64 {
+        0.2126f64 * self.red + 0.7152f64 * self.green + 0.0722f64 * self.blue
+    }
+
     pub fn inverse(&self) -> Color {
         Color::from(1f64 - self.red, 1f64 - self.green, 1f64 - self.blue, self.alpha)
     }
@@ -83,6 +91,14 @@ impl Mul<f64> for Color {
     }
 }
 
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, rhs: Color) -> Self::Output {
+        Color { red: self.red * rhs.red, green: self.green * rhs.green, blue: self.blue * rhs.blue, alpha: self.alpha * rhs.alpha }
+    }
+}
+
 impl Div<f64> for Color {
     type Output = Color;
 
@@ -0,0 +1,30 @@
+use crate::basis::Basis;
+use crate::projection::{forward_of, right_of, Projection};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+pub struct CylindricalProjection {
+    horizontal_field_of_view: f64,
+    vertical_field_of_view: f64
+}
+
+impl CylindricalProjection {
+    pub fn from(horizontal_field_of_view: f64, vertical_field_of_view: f64) -> Option<CylindricalProjection> {
+        if horizontal_field_of_view <= 0f64 || vertical_field_of_view <= 0f64 || vertical_field_of_view >= std::f64::consts::PI {
+            return None;
+        }
+
+        Some(CylindricalProjection { horizontal_field_of_view, vertical_field_of_view })
+    }
+}
+
+impl Projection for CylindricalProjection {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, _aspect_ratio: f64, _random: &mut Random) -> Option<Ray> {
+        let angle = screen_x * self.horizontal_field_of_view / 2f64;
+        let height = screen_y * (self.vertical_field_of_view / 2f64).tan();
+
+        let direction = forward_of(basis) * angle.cos() + right_of(basis) * angle.sin() + basis.v * height;
+        Ray::from(position, direction)
+    }
+}
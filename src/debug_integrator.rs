@@ -0,0 +1,41 @@
+use crate::color::Color;
+use crate::integrator::Integrator;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::surface::RayIntersectionResult;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DebugMode {
+    Normals,
+    Depth { max_distance: f64 },
+    Albedo
+}
+
+pub struct DebugIntegrator {
+    mode: DebugMode
+}
+
+impl DebugIntegrator {
+    pub fn from(mode: DebugMode) -> DebugIntegrator {
+        DebugIntegrator { mode }
+    }
+}
+
+impl Integrator for DebugIntegrator {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, _random: &mut Random) -> Color {
+        let (surface_material, surface_normal, t) = match scene.surface.intersect_with_ray(ray) {
+            RayIntersectionResult::Intersected { surface_material, surface_normal, t, .. } => (surface_material, surface_normal, t),
+            RayIntersectionResult::NoIntersection => return Color::BLACK
+        };
+
+        match self.mode {
+            DebugMode::Normals => Color::from((surface_normal.x + 1f64) / 2f64, (surface_normal.y + 1f64) / 2f64, (surface_normal.z + 1f64) / 2f64, 1f64),
+            DebugMode::Depth { max_distance } => {
+                let closeness = (1f64 - t / max_distance).clamp(0f64, 1f64);
+                Color::from(closeness, closeness, closeness, 1f64)
+            },
+            DebugMode::Albedo => surface_material.color
+        }
+    }
+}
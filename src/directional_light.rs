@@ -1,6 +1,10 @@
+use std::f64::consts::PI;
+
 use crate::color::Color;
 
-use crate::light_source::LightSource;
+use crate::light_source::{self, LightSource};
+use crate::random::Random;
+use crate::ray::Ray;
 use crate::vector::Vector;
 
 pub struct DirectionalLight {
@@ -22,7 +26,19 @@ impl LightSource for DirectionalLight {
         self.direction
     }
 
-    fn get_light_color(&self) -> Color {
+    fn get_light_distance_from(&self, _point: Vector) -> f64 {
+        f64::INFINITY
+    }
+
+    fn get_light_color(&self, _point: Vector) -> Color {
         self.color
     }
+
+    fn get_power(&self) -> f64 {
+        self.color.luminance() * PI
+    }
+
+    fn emit_photon(&self, scene_center: Vector, scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+        light_source::emit_photon_towards_scene(self.direction, self.color, scene_center, scene_radius, random)
+    }
 }
@@ -0,0 +1,109 @@
+pub struct Distribution1D {
+    values: Vec<f64>,
+    cdf: Vec<f64>,
+    total: f64
+}
+
+impl Distribution1D {
+    pub fn from(values: &[f64]) -> Option<Distribution1D> {
+        if values.is_empty() || values.iter().any(|value| !value.is_finite() || *value < 0f64) {
+            return None;
+        }
+
+        let mut cdf = Vec::with_capacity(values.len() + 1);
+        cdf.push(0f64);
+
+        for value in values {
+            cdf.push(cdf[cdf.len() - 1] + value);
+        }
+
+        let total = cdf[values.len()];
+        if total <= 0f64 {
+            return None;
+        }
+
+        for entry in cdf.iter_mut() {
+            *entry /= total;
+        }
+
+        Some(Distribution1D { values: values.to_vec(), cdf, total })
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    pub fn probability(&self, index: usize) -> f64 {
+        match self.values.get(index) {
+            Some(value) => value / self.total,
+            None => 0f64
+        }
+    }
+
+    pub fn sample_index(&self, random_value: f64) -> (usize, f64) {
+        let index = (self.cdf.partition_point(|entry| *entry <= random_value) - 1).min(self.len() - 1);
+        (index, self.probability(index))
+    }
+
+    pub fn sample_continuous(&self, random_value: f64) -> (f64, f64) {
+        let (index, probability) = self.sample_index(random_value);
+        let segment = self.cdf[index + 1] - self.cdf[index];
+        let offset = if segment > 0f64 { ((random_value - self.cdf[index]) / segment).clamp(0f64, 1f64) } else { 0.5f64 };
+
+        ((index as f64 + offset) / self.len() as f64, probability * self.len() as f64)
+    }
+
+    pub fn density_at(&self, position: f64) -> f64 {
+        let index = ((position * self.len() as f64) as usize).min(self.len() - 1);
+        self.probability(index) * self.len() as f64
+    }
+}
+
+pub struct Distribution2D {
+    conditionals: Vec<Distribution1D>,
+    marginal: Distribution1D
+}
+
+impl Distribution2D {
+    pub fn from(values: &[f64], width: usize, height: usize) -> Option<Distribution2D> {
+        if width == 0 || height == 0 || values.len() != width * height {
+            return None;
+        }
+
+        // empty rows still get a distribution so every row can be indexed, the marginal never picks them
+        let conditionals: Vec<Distribution1D> = values.chunks(width)
+            .map(|row| Distribution1D::from(row).or_else(|| Distribution1D::from(&vec![1f64; width])))
+            .collect::<Option<Vec<_>>>()?;
+
+        let row_totals: Vec<f64> = values.chunks(width).map(|row| row.iter().sum()).collect();
+        let marginal = Distribution1D::from(&row_totals)?;
+
+        Some(Distribution2D { conditionals, marginal })
+    }
+
+    pub fn sample(&self, first_random_value: f64, second_random_value: f64) -> (f64, f64, f64) {
+        let (v, marginal_density) = self.marginal.sample_continuous(first_random_value);
+        let row = ((v * self.marginal.len() as f64) as usize).min(self.marginal.len() - 1);
+        let (u, conditional_density) = self.conditionals[row].sample_continuous(second_random_value);
+
+        (u, v, marginal_density * conditional_density)
+    }
+
+    pub fn density_at(&self, u: f64, v: f64) -> f64 {
+        let row = ((v * self.marginal.len() as f64) as usize).min(self.marginal.len() - 1);
+
+        if self.marginal.probability(row) <= 0f64 {
+            return 0f64;
+        }
+
+        self.marginal.density_at(v) * self.conditionals[row].density_at(u)
+    }
+}
@@ -0,0 +1,17 @@
+use crate::color::Color;
+use crate::light_source::{self, LightSource};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+pub const UP: Vector = Vector { x: 0f64, y: 1f64, z: 0f64 };
+
+pub trait Environment: Sync {
+    fn get_radiance(&self, direction: Vector) -> Color;
+}
+
+// each photon acts like a directional light coming from a direction the environment's own light sampling picked
+pub fn emit_photon(environment: &dyn LightSource, scene_center: Vector, scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+    let light_sample = environment.sample_from(scene_center, random);
+    light_source::emit_photon_towards_scene(light_sample.direction, light_sample.color, scene_center, scene_radius, random)
+}
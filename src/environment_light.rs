@@ -0,0 +1,195 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+use crate::distribution::Distribution2D;
+use crate::environment::{self, Environment, UP};
+use crate::light_source::{LightSample, LightSource};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::screen::Screen;
+use crate::vector::Vector;
+
+pub struct EnvironmentLight {
+    image: Screen,
+    distribution: Distribution2D,
+    average_radiance: Color,
+    brightest_direction: Vector,
+    pub intensity: f64,
+    pub rotation: f64,
+    pub shadow_sample_count: usize
+}
+
+impl EnvironmentLight {
+    pub fn from(image: Screen, intensity: f64, shadow_sample_count: usize) -> Option<EnvironmentLight> {
+        if intensity < 0f64 || shadow_sample_count == 0 {
+            return None;
+        }
+
+        let (width, height) = image.resolution();
+
+        // rows near the poles cover less solid angle, so their pixels are picked less often
+        let weights: Vec<f64> = image.pixel_positions()
+            .map(|(x, y)| {
+                let sin_theta = (PI * (y as f64 + 0.5f64) / height as f64).sin();
+                image.get_color_at(x, y).map_or(0f64, |color| color.luminance().max(0f64)) * sin_theta
+            })
+            .collect();
+
+        let distribution = Distribution2D::from(&weights, width, height)?;
+
+        let (brightest_index, _) = weights.iter().enumerate().fold((0, 0f64), |brightest, (index, weight)| if *weight > brightest.1 { (index, *weight) } else { brightest });
+        let brightest_direction = EnvironmentLight::get_direction_of((brightest_index % width) as f64 + 0.5f64, (brightest_index / width) as f64 + 0.5f64, width, height);
+
+        let weight_total: f64 = (0..height).map(|y| (PI * (y as f64 + 0.5f64) / height as f64).sin() * width as f64).sum();
+        let average_radiance = image.pixel_positions()
+            .filter_map(|(x, y)| image.get_color_at(x, y).map(|color| color * ((PI * (y as f64 + 0.5f64) / height as f64).sin() / weight_total)))
+            .fold(Color::ZERO, |sum, color| sum + color);
+
+        Some(EnvironmentLight { image, distribution, average_radiance, brightest_direction, intensity, rotation: 0f64, shadow_sample_count })
+    }
+
+    // the image center looks down +z, the top row is straight up
+    fn get_direction_of(pixel_x: f64, pixel_y: f64, width: usize, height: usize) -> Vector {
+        let longitude = (pixel_x / width as f64 - 0.5f64) * 2f64 * PI;
+        let latitude = (0.5f64 - pixel_y / height as f64) * PI;
+
+        Vector::from(latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos())
+    }
+
+    fn get_image_position_of(&self, direction: Vector) -> (f64, f64) {
+        let direction = direction.rotated(-self.rotation, UP);
+        let longitude = direction.x.atan2(direction.z);
+        let latitude = direction.y.clamp(-1f64, 1f64).asin();
+
+        ((0.5f64 + longitude / (2f64 * PI)).rem_euclid(1f64), (0.5f64 - latitude / PI).clamp(0f64, 1f64))
+    }
+
+    // the density sample_from picks the direction with, per steradian
+    pub fn get_pdf(&self, direction: Vector) -> f64 {
+        let (u, v) = self.get_image_position_of(direction);
+        let sin_theta = (v * PI).sin();
+
+        if sin_theta <= 0f64 {
+            return 0f64;
+        }
+
+        // the image density is per unit of image area, each image row spans a band of 2 pi sin(theta) steradians per unit
+        self.distribution.density_at(u, v) / (2f64 * PI * PI * sin_theta)
+    }
+}
+
+impl Environment for EnvironmentLight {
+    fn get_radiance(&self, direction: Vector) -> Color {
+        let (width, height) = self.image.resolution();
+        let (u, v) = self.get_image_position_of(direction);
+        let x = ((u * width as f64) as usize).min(width - 1);
+        let y = ((v * height as f64) as usize).min(height - 1);
+
+        self.image.get_color_at(x, y).unwrap_or(Color::BLACK) * self.intensity
+    }
+}
+
+impl LightSource for EnvironmentLight {
+    fn get_light_direction_from(&self, _point: Vector) -> Vector {
+        -self.brightest_direction.rotated(self.rotation, UP)
+    }
+
+    fn get_light_distance_from(&self, _point: Vector) -> f64 {
+        f64::INFINITY
+    }
+
+    // a uniformly lit sky gives a facing surface pi times its radiance
+    fn get_light_color(&self, _point: Vector) -> Color {
+        self.average_radiance * (PI * self.intensity)
+    }
+
+    fn get_shadow_sample_count(&self) -> usize {
+        self.shadow_sample_count
+    }
+
+    fn emit_photon(&self, scene_center: Vector, scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+        environment::emit_photon(self, scene_center, scene_radius, random)
+    }
+
+    fn sample_from(&self, _point: Vector, random: &mut Random) -> LightSample {
+        let (width, height) = self.image.resolution();
+        let (u, v, _) = self.distribution.sample(random.next_f64(), random.next_f64());
+        let direction = EnvironmentLight::get_direction_of(u * width as f64, v * height as f64, width, height).rotated(self.rotation, UP);
+
+        let direction_density = self.get_pdf(direction);
+        let color = if direction_density > 0f64 { self.get_radiance(direction) / direction_density } else { Color::ZERO };
+
+        LightSample { direction: -direction, distance: f64::INFINITY, color }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 4;
+    const HEIGHT: usize = 3;
+    const SAMPLE_COUNT: usize = 200000;
+
+    fn light() -> EnvironmentLight {
+        let mut image = Screen::from(WIDTH, HEIGHT, Color::from(0.1f64, 0.1f64, 0.1f64, 1f64));
+        image.paint_at(1, 0, Color::from(2f64, 2f64, 2f64, 1f64)).unwrap();
+        image.paint_at(2, 1, Color::from(8f64, 8f64, 8f64, 1f64)).unwrap();
+        image.paint_at(3, 2, Color::BLACK).unwrap();
+
+        EnvironmentLight::from(image, 1f64, 1).unwrap()
+    }
+
+    fn pixel_of(light: &EnvironmentLight, direction: Vector) -> usize {
+        let (u, v) = light.get_image_position_of(direction);
+        ((v * HEIGHT as f64) as usize).min(HEIGHT - 1) * WIDTH + ((u * WIDTH as f64) as usize).min(WIDTH - 1)
+    }
+
+    #[test]
+    fn samples_follow_the_pdf() {
+        let light = light();
+        let mut random = Random::from_seed(1);
+        let mut counts = [0usize; WIDTH * HEIGHT];
+
+        for _ in 0..SAMPLE_COUNT {
+            counts[pixel_of(&light, -light.sample_from(Vector::ZERO, &mut random).direction)] += 1;
+        }
+
+        // integrates the pdf over each pixel's patch of the sphere with a fine grid of solid angle elements
+        let steps = 64;
+        let mut expected = [0f64; WIDTH * HEIGHT];
+        for y in 0..HEIGHT * steps {
+            for x in 0..WIDTH * steps {
+                let (pixel_x, pixel_y) = ((x as f64 + 0.5f64) / steps as f64, (y as f64 + 0.5f64) / steps as f64);
+                let direction = EnvironmentLight::get_direction_of(pixel_x, pixel_y, WIDTH, HEIGHT);
+                let solid_angle = (2f64 * PI / (WIDTH * steps) as f64) * (PI / (HEIGHT * steps) as f64) * (PI * pixel_y / HEIGHT as f64).sin();
+
+                expected[pixel_of(&light, direction)] += light.get_pdf(direction) * solid_angle;
+            }
+        }
+
+        assert!((expected.iter().sum::<f64>() - 1f64).abs() < 1e-3, "{:?}", expected);
+        for (count, probability) in counts.iter().zip(expected) {
+            assert!((*count as f64 / SAMPLE_COUNT as f64 - probability).abs() < 0.005, "{:?} {:?}", counts, expected);
+        }
+        assert_eq!(counts[WIDTH * HEIGHT - 1], 0);
+    }
+
+    #[test]
+    fn samples_estimate_the_irradiance_integral() {
+        let light = light();
+        let mut random = Random::from_seed(2);
+
+        let mean = (0..SAMPLE_COUNT).map(|_| light.sample_from(Vector::ZERO, &mut random).color.red).sum::<f64>() / SAMPLE_COUNT as f64;
+
+        // each pixel covers 2 pi / width of longitude between the cosines of its row's polar angles
+        let expected: f64 = light.image.pixel_positions()
+            .map(|(x, y)| {
+                let (top, bottom) = ((PI * y as f64 / HEIGHT as f64).cos(), (PI * (y + 1) as f64 / HEIGHT as f64).cos());
+                light.image.get_color_at(x, y).unwrap().red * 2f64 * PI / WIDTH as f64 * (top - bottom)
+            })
+            .sum();
+
+        assert!((mean - expected).abs() < expected * 0.01, "{} {}", mean, expected);
+    }
+}
@@ -0,0 +1,56 @@
+use std::f64::consts::PI;
+
+use crate::basis::Basis;
+use crate::projection::{forward_of, right_of, Projection};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+pub struct EquirectangularProjection;
+
+impl EquirectangularProjection {
+    pub fn from() -> EquirectangularProjection {
+        EquirectangularProjection
+    }
+}
+
+impl Projection for EquirectangularProjection {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, _aspect_ratio: f64, _random: &mut Random) -> Option<Ray> {
+        let longitude = screen_x * PI;
+        let latitude = screen_y * PI / 2f64;
+
+        let direction = forward_of(basis) * (latitude.cos() * longitude.cos()) + right_of(basis) * (latitude.cos() * longitude.sin()) + basis.v * latitude.sin();
+        Ray::from(position, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn direction_at(basis: &Basis, screen_x: f64, screen_y: f64) -> Vector {
+        EquirectangularProjection::from().generate_ray(basis, Vector::ZERO, screen_x, screen_y, 2f64, &mut Random::from_seed(1)).unwrap().direction
+    }
+
+    #[test]
+    fn screen_covers_the_whole_sphere() {
+        let basis = Basis::from_wv(Vector::from(1f64, 2f64, 3f64), Vector::from(0f64, 1f64, 0f64)).unwrap();
+
+        // the horizontal edges meet behind the camera, the vertical ones are the poles
+        assert!((direction_at(&basis, 0.5f64, 0f64) - right_of(&basis)).length() < 1e-9);
+        assert!((direction_at(&basis, -0.5f64, 0f64) + right_of(&basis)).length() < 1e-9);
+        assert!((direction_at(&basis, 1f64, 0f64) + forward_of(&basis)).length() < 1e-9);
+        assert!((direction_at(&basis, -1f64, 0f64) + forward_of(&basis)).length() < 1e-9);
+        assert!((direction_at(&basis, 0.3f64, 1f64) - basis.v).length() < 1e-9);
+        assert!((direction_at(&basis, -0.7f64, -1f64) + basis.v).length() < 1e-9);
+    }
+
+    #[test]
+    fn latitude_and_longitude_grow_linearly() {
+        let basis = Basis::from_w(Vector::from(0f64, 0f64, 1f64)).unwrap();
+        let direction = direction_at(&basis, 0.25f64, 0.5f64);
+
+        assert!((direction.dot(basis.v).asin() - PI / 4f64).abs() < 1e-9);
+        assert!((direction.dot(right_of(&basis)).atan2(direction.dot(forward_of(&basis))) - PI / 4f64).abs() < 1e-9);
+    }
+}
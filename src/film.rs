@@ -0,0 +1,235 @@
+use crate::color::Color;
+use crate::filter::Filter;
+use crate::screen::{Screen, Tile};
+
+pub struct Film {
+    width: usize,
+    height: usize,
+    color_sums: Vec<Color>,
+    weight_sums: Vec<f64>,
+    sample_counts: Vec<usize>,
+    luminance_sums: Vec<f64>,
+    luminance_squared_sums: Vec<f64>
+}
+
+impl Film {
+    pub fn from(width: usize, height: usize) -> Film {
+        Film {
+            width,
+            height,
+            color_sums: vec![Color::ZERO; width * height],
+            weight_sums: vec![0f64; width * height],
+            sample_counts: vec![0; width * height],
+            luminance_sums: vec![0f64; width * height],
+            luminance_squared_sums: vec![0f64; width * height]
+        }
+    }
+
+    pub fn resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn merge_tile(&mut self, film_tile: &FilmTile) {
+        let bounds = film_tile.bounds;
+
+        for (index, (x, y)) in bounds.pixel_positions().enumerate() {
+            let film_index = self.width * y + x;
+            self.color_sums[film_index] = self.color_sums[film_index] + film_tile.color_sums[index];
+            self.weight_sums[film_index] += film_tile.weight_sums[index];
+            self.sample_counts[film_index] += film_tile.sample_counts[index];
+            self.luminance_sums[film_index] += film_tile.luminance_sums[index];
+            self.luminance_squared_sums[film_index] += film_tile.luminance_squared_sums[index];
+        }
+    }
+
+    pub fn get_sample_count_at(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.sample_counts[self.width * y + x])
+    }
+
+    // the sample count, luminance sum and squared luminance sum of the samples taken inside each pixel so far
+    pub fn luminance_statistics(&self) -> Vec<(usize, f64, f64)> {
+        (0..self.width * self.height).map(|index| (self.sample_counts[index], self.luminance_sums[index], self.luminance_squared_sums[index])).collect()
+    }
+
+    pub fn sample_count_screen(&self) -> Screen {
+        let mut screen = Screen::from(self.width, self.height, Color::BLACK);
+        let max_count = self.sample_counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+
+        for (x, y) in screen.pixel_positions() {
+            let intensity = self.sample_counts[self.width * y + x] as f64 / max_count;
+            screen.paint_at(x, y, Color::from(intensity, intensity, intensity, 1f64)).expect("should not go wrong");
+        }
+
+        screen
+    }
+
+    pub fn get_color_at(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let index = self.width * y + x;
+        let weight = self.weight_sums[index];
+
+        // negative filter lobes can leave a pixel with a negative or vanishing weight, the latter has nothing left to show
+        if weight.abs() <= f64::EPSILON {
+            return Some(Color::BLACK);
+        }
+
+        let sum = self.color_sums[index];
+        Some(Color::from((sum.red / weight).max(0f64), (sum.green / weight).max(0f64), (sum.blue / weight).max(0f64), (sum.alpha / weight).max(0f64)))
+    }
+
+    pub fn develop_into(&self, screen: &mut Screen) {
+        for (x, y) in screen.pixel_positions() {
+            if let Some(color) = self.get_color_at(x, y) {
+                screen.paint_at(x, y, color).expect("should not go wrong");
+            }
+        }
+    }
+}
+
+pub struct FilmTile {
+    bounds: Tile,
+    color_sums: Vec<Color>,
+    weight_sums: Vec<f64>,
+    sample_counts: Vec<usize>,
+    luminance_sums: Vec<f64>,
+    luminance_squared_sums: Vec<f64>
+}
+
+impl FilmTile {
+    pub fn from(tile: Tile, film_width: usize, film_height: usize, filter: &dyn Filter) -> FilmTile {
+        let margin = (filter.radius() + 0.5f64).ceil() as usize;
+
+        let x = tile.x.saturating_sub(margin);
+        let y = tile.y.saturating_sub(margin);
+        let width = (tile.x + tile.width + margin).min(film_width) - x;
+        let height = (tile.y + tile.height + margin).min(film_height) - y;
+
+        FilmTile {
+            bounds: Tile { x, y, width, height },
+            color_sums: vec![Color::ZERO; width * height],
+            weight_sums: vec![0f64; width * height],
+            sample_counts: vec![0; width * height],
+            luminance_sums: vec![0f64; width * height],
+            luminance_squared_sums: vec![0f64; width * height]
+        }
+    }
+
+    pub fn add_sample(&mut self, film_x: f64, film_y: f64, color: Color, filter: &dyn Filter) {
+        let radius = filter.radius();
+        let bounds = self.bounds;
+
+        let (sample_x, sample_y) = (film_x.floor(), film_y.floor());
+        if sample_x >= bounds.x as f64 && sample_y >= bounds.y as f64 && sample_x < (bounds.x + bounds.width) as f64 && sample_y < (bounds.y + bounds.height) as f64 {
+            let index = bounds.width * (sample_y as usize - bounds.y) + (sample_x as usize - bounds.x);
+            self.sample_counts[index] += 1;
+            self.luminance_sums[index] += color.luminance();
+            self.luminance_squared_sums[index] += color.luminance().powi(2);
+        }
+
+        let min_x = ((film_x - 0.5f64 - radius).floor() + 1f64).max(bounds.x as f64) as usize;
+        let max_x = (film_x - 0.5f64 + radius).floor().min((bounds.x + bounds.width) as f64 - 1f64);
+        let min_y = ((film_y - 0.5f64 - radius).floor() + 1f64).max(bounds.y as f64) as usize;
+        let max_y = (film_y - 0.5f64 + radius).floor().min((bounds.y + bounds.height) as f64 - 1f64);
+
+        if max_x < 0f64 || max_y < 0f64 {
+            return;
+        }
+
+        for y in min_y..=max_y as usize {
+            for x in min_x..=max_x as usize {
+                let weight = filter.evaluate(x as f64 + 0.5f64 - film_x, y as f64 + 0.5f64 - film_y);
+
+                if weight == 0f64 {
+                    continue;
+                }
+
+                let index = bounds.width * (y - bounds.y) + (x - bounds.x);
+                let sum = self.color_sums[index];
+                self.color_sums[index] = Color::from(sum.red + color.red * weight, sum.green + color.green * weight, sum.blue + color.blue * weight, sum.alpha + color.alpha * weight);
+                self.weight_sums[index] += weight;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::{MitchellFilter, TentFilter};
+
+    // covers only the pixel the sample lands in, with a negative weight
+    struct NegativeFilter;
+
+    impl Filter for NegativeFilter {
+        fn radius(&self) -> f64 {
+            0.5f64
+        }
+
+        fn evaluate_1d(&self, _x: f64) -> f64 {
+            -1f64
+        }
+
+        fn evaluate(&self, _x: f64, _y: f64) -> f64 {
+            -1f64
+        }
+    }
+
+    fn film_with_sample(color: Color, sample_count: usize) -> Film {
+        let mut film = Film::from(1, 1);
+        let mut film_tile = FilmTile::from(Tile { x: 0, y: 0, width: 1, height: 1 }, 1, 1, &NegativeFilter);
+
+        for _ in 0..sample_count {
+            film_tile.add_sample(0.5f64, 0.5f64, color, &NegativeFilter);
+        }
+
+        film.merge_tile(&film_tile);
+        film
+    }
+
+    #[test]
+    fn overlapping_filters_keep_a_flat_color_flat() {
+        let color = Color::from(0.5f64, 0.25f64, 1f64, 1f64);
+
+        for filter in [Box::new(TentFilter::from(1.5f64).unwrap()) as Box<dyn Filter>, Box::new(MitchellFilter::from(2f64, 1f64 / 3f64, 1f64 / 3f64).unwrap())] {
+            let mut film = Film::from(4, 4);
+            let mut film_tile = FilmTile::from(Tile { x: 0, y: 0, width: 4, height: 4 }, 4, 4, filter.as_ref());
+
+            for y in 0..16 {
+                for x in 0..16 {
+                    film_tile.add_sample((x as f64 + 0.5f64) / 4f64, (y as f64 + 0.5f64) / 4f64, color, filter.as_ref());
+                }
+            }
+
+            film.merge_tile(&film_tile);
+
+            for (x, y) in Screen::from(4, 4, Color::BLACK).pixel_positions() {
+                let developed = film.get_color_at(x, y).unwrap();
+                assert!((developed.red - 0.5f64).abs() < 1e-9 && (developed.green - 0.25f64).abs() < 1e-9 && (developed.blue - 1f64).abs() < 1e-9);
+                assert_eq!(film.get_sample_count_at(x, y), Some(16));
+            }
+        }
+    }
+
+    #[test]
+    fn negative_weights_still_divide_out() {
+        let color = film_with_sample(Color::from(0.5f64, 0.25f64, 1f64, 1f64), 1).get_color_at(0, 0).unwrap();
+
+        assert_eq!((color.red, color.green, color.blue), (0.5f64, 0.25f64, 1f64));
+    }
+
+    #[test]
+    fn pixels_without_weight_are_developed_black() {
+        let mut screen = Screen::from(1, 1, Color::WHITE);
+        film_with_sample(Color::WHITE, 0).develop_into(&mut screen);
+
+        let color = screen.get_color_at(0, 0).unwrap();
+        assert_eq!((color.red, color.green, color.blue), (0f64, 0f64, 0f64));
+    }
+}
@@ -0,0 +1,229 @@
+use std::f64::consts::PI;
+
+pub trait Filter: Sync {
+    fn radius(&self) -> f64;
+    fn evaluate_1d(&self, x: f64) -> f64;
+
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+}
+
+pub struct BoxFilter {
+    radius: f64
+}
+
+impl BoxFilter {
+    pub fn from(radius: f64) -> Option<BoxFilter> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Some(BoxFilter { radius })
+    }
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        if x.abs() <= self.radius { 1f64 } else { 0f64 }
+    }
+}
+
+pub struct TentFilter {
+    radius: f64
+}
+
+impl TentFilter {
+    pub fn from(radius: f64) -> Option<TentFilter> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Some(TentFilter { radius })
+    }
+}
+
+impl Filter for TentFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        (self.radius - x.abs()).max(0f64)
+    }
+}
+
+pub struct GaussianFilter {
+    radius: f64,
+    alpha: f64,
+    edge_value: f64
+}
+
+impl GaussianFilter {
+    pub fn from(radius: f64, alpha: f64) -> Option<GaussianFilter> {
+        if radius <= 0f64 || alpha <= 0f64 {
+            return None;
+        }
+
+        Some(GaussianFilter { radius, alpha, edge_value: (-alpha * radius * radius).exp() })
+    }
+}
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        ((-self.alpha * x * x).exp() - self.edge_value).max(0f64)
+    }
+}
+
+pub struct MitchellFilter {
+    radius: f64,
+    b: f64,
+    c: f64
+}
+
+impl MitchellFilter {
+    pub fn from(radius: f64, b: f64, c: f64) -> Option<MitchellFilter> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Some(MitchellFilter { radius, b, c })
+    }
+}
+
+impl Filter for MitchellFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = (2f64 * x / self.radius).abs();
+        let (b, c) = (self.b, self.c);
+
+        let value = if x > 2f64 {
+            0f64
+        } else if x > 1f64 {
+            (-b - 6f64 * c) * x.powi(3) + (6f64 * b + 30f64 * c) * x.powi(2) + (-12f64 * b - 48f64 * c) * x + (8f64 * b + 24f64 * c)
+        } else {
+            (12f64 - 9f64 * b - 6f64 * c) * x.powi(3) + (-18f64 + 12f64 * b + 6f64 * c) * x.powi(2) + (6f64 - 2f64 * b)
+        };
+
+        value / 6f64
+    }
+}
+
+pub struct LanczosFilter {
+    radius: f64
+}
+
+impl LanczosFilter {
+    pub fn from(radius: f64) -> Option<LanczosFilter> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Some(LanczosFilter { radius })
+    }
+
+    fn sinc(x: f64) -> f64 {
+        if x.abs() < 1e-5 {
+            return 1f64;
+        }
+
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        if x.abs() > self.radius {
+            return 0f64;
+        }
+
+        LanczosFilter::sinc(x) * LanczosFilter::sinc(x / self.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> Vec<Box<dyn Filter>> {
+        vec![
+            Box::new(BoxFilter::from(0.5f64).unwrap()),
+            Box::new(TentFilter::from(1.5f64).unwrap()),
+            Box::new(GaussianFilter::from(1.5f64, 2f64).unwrap()),
+            Box::new(MitchellFilter::from(2f64, 1f64 / 3f64, 1f64 / 3f64).unwrap()),
+            Box::new(LanczosFilter::from(3f64).unwrap())
+        ]
+    }
+
+    #[test]
+    fn filters_peak_at_their_center() {
+        let expected_peaks = [1f64, 1.5f64, 1f64 - (-4.5f64).exp(), 8f64 / 9f64, 1f64];
+
+        for (filter, expected_peak) in filters().iter().zip(expected_peaks) {
+            assert!((filter.evaluate_1d(0f64) - expected_peak).abs() < 1e-12, "{} against {}", filter.evaluate_1d(0f64), expected_peak);
+
+            for step in 1..20 {
+                let x = filter.radius() * step as f64 / 20f64;
+                assert!(filter.evaluate_1d(x) <= filter.evaluate_1d(0f64) && filter.evaluate_1d(x) == filter.evaluate_1d(-x));
+            }
+        }
+    }
+
+    #[test]
+    fn filters_vanish_at_and_beyond_their_radius() {
+        for filter in filters() {
+            let radius = filter.radius();
+
+            assert!(filter.evaluate_1d(radius * 1.01f64) == 0f64 && filter.evaluate_1d(-radius * 1.5f64) == 0f64);
+            assert!(filter.evaluate(radius * 2f64, 0f64) == 0f64);
+        }
+
+        // the box filter still covers its edge, the smooth ones fall to zero right there
+        assert_eq!(BoxFilter::from(0.5f64).unwrap().evaluate_1d(0.5f64), 1f64);
+        for filter in filters().iter().skip(1) {
+            assert!(filter.evaluate_1d(filter.radius()).abs() < 1e-12, "{}", filter.evaluate_1d(filter.radius()));
+        }
+    }
+
+    #[test]
+    fn mitchell_filter_has_negative_lobes() {
+        let filter = MitchellFilter::from(2f64, 1f64 / 3f64, 1f64 / 3f64).unwrap();
+
+        assert!(filter.evaluate_1d(0.5f64) > 0f64);
+        assert!(filter.evaluate_1d(1.5f64) < 0f64 && filter.evaluate_1d(-1.5f64) < 0f64);
+
+        // the outer polynomial piece starts positive and crosses zero a little past halfway out
+        assert!(filter.evaluate_1d(1.1f64) > 0f64 && filter.evaluate_1d(1.2f64) < 0f64);
+        assert!(filter.evaluate_1d(1.99f64) < 0f64);
+    }
+
+    #[test]
+    fn lanczos_filter_rings_between_its_zero_crossings() {
+        let filter = LanczosFilter::from(3f64).unwrap();
+
+        assert!(filter.evaluate_1d(1f64).abs() < 1e-12 && filter.evaluate_1d(2f64).abs() < 1e-12);
+        assert!(filter.evaluate_1d(1.5f64) < 0f64 && filter.evaluate_1d(2.5f64) > 0f64);
+    }
+
+    #[test]
+    fn filters_are_separable() {
+        for filter in filters() {
+            assert_eq!(filter.evaluate(0.3f64, -0.2f64), filter.evaluate_1d(0.3f64) * filter.evaluate_1d(-0.2f64));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::basis::Basis;
+use crate::projection::{forward_of, right_of, Projection};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+pub struct FisheyeProjection {
+    field_of_view: f64
+}
+
+impl FisheyeProjection {
+    pub fn from(field_of_view: f64) -> Option<FisheyeProjection> {
+        if field_of_view <= 0f64 || field_of_view > 2f64 * std::f64::consts::PI {
+            return None;
+        }
+
+        Some(FisheyeProjection { field_of_view })
+    }
+}
+
+impl Projection for FisheyeProjection {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, aspect_ratio: f64, _random: &mut Random) -> Option<Ray> {
+        // equidistant mapping inside the circle that fits the shorter side of the screen
+        let (x, y) = if aspect_ratio >= 1f64 { (screen_x * aspect_ratio, screen_y) } else { (screen_x, screen_y / aspect_ratio) };
+        let radius = (x * x + y * y).sqrt();
+
+        if radius > 1f64 {
+            return None;
+        }
+
+        let polar_angle = radius * self.field_of_view / 2f64;
+        let azimuth = y.atan2(x);
+
+        let direction = forward_of(basis) * polar_angle.cos() + (right_of(basis) * azimuth.cos() + basis.v * azimuth.sin()) * polar_angle.sin();
+        Ray::from(position, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    fn angle_from_forward(projection: &FisheyeProjection, basis: &Basis, screen_x: f64, screen_y: f64, aspect_ratio: f64) -> Option<f64> {
+        projection.generate_ray(basis, Vector::ZERO, screen_x, screen_y, aspect_ratio, &mut Random::from_seed(1))
+            .map(|ray| ray.direction.dot(forward_of(basis)).clamp(-1f64, 1f64).acos())
+    }
+
+    #[test]
+    fn image_circle_edge_is_half_the_field_of_view_away() {
+        let basis = Basis::from_w(Vector::from(0f64, 0f64, 1f64)).unwrap();
+
+        for field_of_view in [PI / 2f64, PI, 1.5f64 * PI] {
+            let projection = FisheyeProjection::from(field_of_view).unwrap();
+
+            for (screen_x, screen_y) in [(1f64, 0f64), (0f64, -1f64), (0.6f64, 0.8f64)] {
+                assert!((angle_from_forward(&projection, &basis, screen_x, screen_y, 1f64).unwrap() - field_of_view / 2f64).abs() < 1e-9);
+            }
+
+            // the angle grows linearly with the distance from the center
+            assert!((angle_from_forward(&projection, &basis, 0.5f64, 0f64, 1f64).unwrap() - field_of_view / 4f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn image_circle_fits_the_shorter_side() {
+        let basis = Basis::from_w(Vector::from(0f64, 0f64, 1f64)).unwrap();
+        let projection = FisheyeProjection::from(PI).unwrap();
+
+        assert!((angle_from_forward(&projection, &basis, 0.5f64, 0f64, 2f64).unwrap() - PI / 2f64).abs() < 1e-9);
+        assert!((angle_from_forward(&projection, &basis, 0f64, 1f64, 2f64).unwrap() - PI / 2f64).abs() < 1e-9);
+        assert!(angle_from_forward(&projection, &basis, 0.6f64, 0f64, 2f64).is_none());
+        assert!(angle_from_forward(&projection, &basis, 0.7f64, 0.8f64, 1f64).is_none());
+    }
+
+    #[test]
+    fn screen_directions_follow_the_basis() {
+        let basis = Basis::from_w(Vector::from(0f64, 0f64, 1f64)).unwrap();
+        let projection = FisheyeProjection::from(PI).unwrap();
+        let mut random = Random::from_seed(1);
+
+        let right = projection.generate_ray(&basis, Vector::ZERO, 1f64, 0f64, 1f64, &mut random).unwrap();
+        let up = projection.generate_ray(&basis, Vector::ZERO, 0f64, 1f64, 1f64, &mut random).unwrap();
+
+        assert!((right.direction - right_of(&basis)).length() < 1e-9);
+        assert!((up.direction - basis.v).length() < 1e-9);
+    }
+}
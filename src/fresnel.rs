@@ -0,0 +1,33 @@
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FresnelModel {
+    Schlick,
+    Exact
+}
+
+impl FresnelModel {
+    pub fn reflectance(&self, cosine_incident: f64, relative_refractive_index: f64) -> f64 {
+        let cosine_incident = cosine_incident.clamp(0f64, 1f64);
+        let sine_transmitted_squared = relative_refractive_index.powi(2) * (1f64 - cosine_incident.powi(2));
+
+        if sine_transmitted_squared >= 1f64 {
+            return 1f64;
+        }
+
+        match self {
+            FresnelModel::Schlick => {
+                let normal_reflectance = ((1f64 - relative_refractive_index) / (1f64 + relative_refractive_index)).powi(2);
+
+                // when leaving the denser medium the approximation has to use the transmitted angle
+                let cosine = if relative_refractive_index > 1f64 { (1f64 - sine_transmitted_squared).sqrt() } else { cosine_incident };
+                normal_reflectance + (1f64 - normal_reflectance) * (1f64 - cosine).powi(5)
+            },
+            FresnelModel::Exact => {
+                let cosine_transmitted = (1f64 - sine_transmitted_squared).sqrt();
+                let parallel = (cosine_incident - relative_refractive_index * cosine_transmitted) / (cosine_incident + relative_refractive_index * cosine_transmitted);
+                let perpendicular = (relative_refractive_index * cosine_incident - cosine_transmitted) / (relative_refractive_index * cosine_incident + cosine_transmitted);
+
+                (parallel.powi(2) + perpendicular.powi(2)) / 2f64
+            }
+        }
+    }
+}
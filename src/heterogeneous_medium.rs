@@ -0,0 +1,152 @@
+use crate::color::Color;
+use crate::medium::{self, Medium};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+use crate::voxel_grid::VoxelGrid;
+
+// once the transmittance gets this low, tracking goes on only half of the time with twice the weight
+const RUSSIAN_ROULETTE_TRANSMITTANCE: f64 = 0.1;
+
+pub struct HeterogeneousMedium {
+    density: VoxelGrid,
+    minimum: Vector,
+    maximum: Vector,
+    // extinction per unit of density, the albedo splits it into scattering and absorption
+    pub extinction: f64,
+    pub albedo: Color,
+    pub asymmetry: f64,
+    // radiance given off per unit length, scaled by a grid covering the same box as the density
+    pub emission: Option<VoxelGrid>,
+    pub emission_color: Color
+}
+
+impl HeterogeneousMedium {
+    pub fn from(density: VoxelGrid, minimum: Vector, maximum: Vector, extinction: f64, albedo: Color, asymmetry: f64) -> Option<HeterogeneousMedium> {
+        if minimum.x >= maximum.x || minimum.y >= maximum.y || minimum.z >= maximum.z || extinction < 0f64 || asymmetry <= -1f64 || asymmetry >= 1f64 {
+            return None;
+        }
+
+        Some(HeterogeneousMedium { density, minimum, maximum, extinction, albedo, asymmetry, emission: None, emission_color: Color::WHITE })
+    }
+
+    fn to_grid_space(&self, point: Vector) -> Vector {
+        let size = self.maximum - self.minimum;
+        let offset = point - self.minimum;
+        Vector::from(offset.x / size.x, offset.y / size.y, offset.z / size.z)
+    }
+
+    fn extinction_at(&self, point: Vector) -> f64 {
+        self.extinction * self.density.sample(self.to_grid_space(point))
+    }
+
+    fn majorant(&self) -> f64 {
+        self.extinction * self.density.max_value()
+    }
+
+    // the part of the interval inside the grid's box
+    fn clip_to_box(&self, ray: Ray, start: f64, end: f64) -> Option<(f64, f64)> {
+        let (mut near, mut far) = (start, end);
+        let axes = [
+            (ray.starting_point.x, ray.direction.x, self.minimum.x, self.maximum.x),
+            (ray.starting_point.y, ray.direction.y, self.minimum.y, self.maximum.y),
+            (ray.starting_point.z, ray.direction.z, self.minimum.z, self.maximum.z)
+        ];
+
+        for (origin, direction, minimum, maximum) in axes {
+            if direction == 0f64 {
+                if origin < minimum || origin > maximum {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let (first, second) = ((minimum - origin) / direction, (maximum - origin) / direction);
+            near = near.max(first.min(second));
+            far = far.min(first.max(second));
+        }
+
+        if near < far { Some((near, far)) } else { None }
+    }
+
+    // the distance to the next tentative collision against the majorant
+    fn step(majorant: f64, random: &mut Random) -> f64 {
+        -(1f64 - random.next_f64()).ln() / majorant
+    }
+}
+
+impl Medium for HeterogeneousMedium {
+    // ratio tracking, every tentative collision keeps the chance that it was not a real one
+    fn get_transmittance(&self, ray: Ray, start: f64, end: f64, random: &mut Random) -> Color {
+        let majorant = self.majorant();
+
+        let (mut t, end) = match self.clip_to_box(ray, start, end) {
+            Some(interval) if majorant > 0f64 => interval,
+            _ => return Color::WHITE
+        };
+
+        let mut transmittance = 1f64;
+
+        loop {
+            t += HeterogeneousMedium::step(majorant, random);
+
+            if t >= end {
+                break;
+            }
+
+            transmittance *= 1f64 - self.extinction_at(ray.point_at(t)) / majorant;
+
+            if transmittance < RUSSIAN_ROULETTE_TRANSMITTANCE {
+                if random.next_f64() < 0.5f64 {
+                    return Color::BLACK;
+                }
+
+                transmittance *= 2f64;
+            }
+        }
+
+        Color::from(transmittance, transmittance, transmittance, 1f64)
+    }
+
+    // delta tracking, a real collision is found with probability extinction times transmittance so only the albedo is left as weight
+    fn sample_scattering(&self, ray: Ray, start: f64, end: f64, random: &mut Random) -> Option<(f64, Color)> {
+        let majorant = self.majorant();
+
+        let (mut t, end) = match self.clip_to_box(ray, start, end) {
+            Some(interval) if majorant > 0f64 => interval,
+            _ => return None
+        };
+
+        loop {
+            t += HeterogeneousMedium::step(majorant, random);
+
+            if t >= end {
+                return None;
+            }
+
+            if random.next_f64() < self.extinction_at(ray.point_at(t)) / majorant {
+                return Some((t, self.albedo));
+            }
+        }
+    }
+
+    // a uniformly picked point, dimmed by the ratio tracked transmittance in front of it
+    fn sample_emission(&self, ray: Ray, start: f64, end: f64, random: &mut Random) -> Option<(f64, Color)> {
+        let emission = self.emission.as_ref()?;
+        let (start, end) = self.clip_to_box(ray, start, end)?;
+
+        let t = start + random.next_f64() * (end - start);
+        let emitted = emission.sample(self.to_grid_space(ray.point_at(t)));
+
+        if emitted <= 0f64 {
+            return None;
+        }
+
+        Some((t, self.emission_color * self.get_transmittance(ray, start, t, random) * (emitted * (end - start))))
+    }
+
+    fn get_phase(&self, cos_theta: f64) -> f64 {
+        medium::henyey_greenstein(cos_theta, self.asymmetry)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::color::Color;
+use crate::medium::{self, Medium};
+use crate::random::Random;
+use crate::ray::Ray;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct HomogeneousMedium {
+    pub absorption: Color,
+    pub scattering: Color,
+    pub asymmetry: f64
+}
+
+impl HomogeneousMedium {
+    pub fn from(absorption: Color, scattering: Color, asymmetry: f64) -> Option<HomogeneousMedium> {
+        if asymmetry <= -1f64 || asymmetry >= 1f64 {
+            return None;
+        }
+
+        Some(HomogeneousMedium { absorption, scattering, asymmetry })
+    }
+
+    pub fn extinction(&self) -> Color {
+        self.absorption + self.scattering
+    }
+
+    fn transmittance_over(&self, distance: f64) -> Color {
+        let extinction = self.extinction();
+        Color::from((-extinction.red * distance).exp(), (-extinction.green * distance).exp(), (-extinction.blue * distance).exp(), 1f64)
+    }
+}
+
+impl Medium for HomogeneousMedium {
+    fn get_transmittance(&self, _ray: Ray, start: f64, end: f64, _random: &mut Random) -> Color {
+        self.transmittance_over((end - start).max(0f64))
+    }
+
+    // distances follow the average extinction, cut off at the end of the interval
+    fn sample_scattering(&self, _ray: Ray, start: f64, end: f64, random: &mut Random) -> Option<(f64, Color)> {
+        let extinction = self.extinction();
+        let sampling_extinction = (extinction.red + extinction.green + extinction.blue) / 3f64;
+        let length = end - start;
+
+        if sampling_extinction <= 0f64 || length <= 0f64 {
+            return None;
+        }
+
+        let reach_probability = 1f64 - (-sampling_extinction * length).exp();
+        let distance = -(1f64 - random.next_f64() * reach_probability).ln() / sampling_extinction;
+        let density = sampling_extinction * (-sampling_extinction * distance).exp() / reach_probability;
+
+        if density <= 0f64 || distance > length {
+            return None;
+        }
+
+        Some((start + distance, self.scattering * self.transmittance_over(distance) / density))
+    }
+
+    fn get_phase(&self, cos_theta: f64) -> f64 {
+        medium::henyey_greenstein(cos_theta, self.asymmetry)
+    }
+}
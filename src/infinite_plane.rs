@@ -6,14 +6,19 @@ use crate::vector::Vector;
 
 pub struct InfinitePlane {
     position: Vector,
+    velocity: Vector,
     normal: Vector,
     material: Material
 }
 
 impl InfinitePlane {
     pub fn from(position: Vector, normal: Vector, material: Material) -> Option<InfinitePlane> {
+        InfinitePlane::moving(position, Vector::ZERO, normal, material)
+    }
+
+    pub fn moving(position: Vector, velocity: Vector, normal: Vector, material: Material) -> Option<InfinitePlane> {
         match normal.normalized() {
-            Some(normal) => Some(InfinitePlane { position, normal, material }),
+            Some(normal) => Some(InfinitePlane { position, velocity, normal, material }),
             _ => None
         }
     }
@@ -21,13 +26,46 @@ impl InfinitePlane {
 
 impl Surface for InfinitePlane {
     fn intersect_with_ray(&self, ray: Ray) -> RayIntersectionResult {
-        let distance: Vector = self.position - ray.starting_point;
+        let distance: Vector = self.position + self.velocity * ray.time - ray.starting_point;
         let t: f64 = distance.dot(self.normal) / ray.direction.dot(self.normal);
 
         if t < f64::EPSILON {
             RayIntersectionResult::NoIntersection
         } else {
-            RayIntersectionResult::Intersected { surface_material: self.material, surface_normal: self.normal, t }
+            RayIntersectionResult::Intersected { surface_material: self.material, surface_normal: self.normal, t, front_face: self.normal.dot(ray.direction) < 0f64 }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn distance_to(plane: &InfinitePlane, ray: Ray) -> Option<f64> {
+        match plane.intersect_with_ray(ray) {
+            RayIntersectionResult::Intersected { t, .. } => Some(t),
+            RayIntersectionResult::NoIntersection => None
         }
     }
+
+    #[test]
+    fn moving_plane_is_hit_where_it_is_at_the_ray_time() {
+        let plane = InfinitePlane::moving(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let ray = Ray::from(Vector::from(0f64, 5f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap();
+
+        assert!((distance_to(&plane, ray).unwrap() - 5f64).abs() < 1e-9);
+        assert!((distance_to(&plane, ray.at_time(2f64)).unwrap() - 3f64).abs() < 1e-9);
+
+        // by then the plane has moved past the ray origin
+        assert!(distance_to(&plane, ray.at_time(6f64)).is_none());
+    }
+
+    #[test]
+    fn sideways_motion_does_not_move_the_plane() {
+        let plane = InfinitePlane::moving(Vector::ZERO, Vector::from(3f64, 0f64, 2f64), Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let ray = Ray::from(Vector::from(0f64, 5f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap();
+
+        assert!((distance_to(&plane, ray.at_time(7f64)).unwrap() - 5f64).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,10 @@
+use crate::color::Color;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::scene::Scene;
+
+pub const SURFACE_OFFSET: f64 = 1e-6;
+
+pub trait Integrator: Sync {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, random: &mut Random) -> Color;
+}
@@ -0,0 +1,157 @@
+use std::f64::consts::PI;
+
+use crate::distribution::Distribution2D;
+use crate::random::Random;
+use crate::sampling;
+use crate::screen::Screen;
+
+pub enum ApertureShape {
+    Circular,
+    Polygonal { blade_count: usize, rotation: f64 },
+    Image(Distribution2D)
+}
+
+impl ApertureShape {
+    pub fn polygonal(blade_count: usize, rotation: f64) -> Option<ApertureShape> {
+        if blade_count < 3 {
+            return None;
+        }
+
+        Some(ApertureShape::Polygonal { blade_count, rotation })
+    }
+
+    pub fn from_image(mask: &Screen) -> Option<ApertureShape> {
+        let (width, height) = mask.resolution();
+        let weights: Vec<f64> = mask.pixel_positions()
+            .map(|(x, y)| mask.get_color_at(x, y).map_or(0f64, |color| color.luminance().max(0f64)))
+            .collect();
+
+        Distribution2D::from(&weights, width, height).map(ApertureShape::Image)
+    }
+
+    pub fn sample(&self, random: &mut Random) -> (f64, f64) {
+        match self {
+            ApertureShape::Circular => sampling::concentric_disk(random),
+            ApertureShape::Polygonal { blade_count, rotation } => {
+                let blade = random.next_usize(*blade_count);
+                let angle = |index: usize| rotation + 2f64 * PI * index as f64 / *blade_count as f64;
+                let (first_angle, second_angle) = (angle(blade), angle(blade + 1));
+
+                // the polygon is a fan of equal triangles around the lens center
+                let (mut a, mut b) = (random.next_f64(), random.next_f64());
+                if a + b > 1f64 {
+                    (a, b) = (1f64 - a, 1f64 - b);
+                }
+
+                (a * first_angle.cos() + b * second_angle.cos(), a * first_angle.sin() + b * second_angle.sin())
+            },
+            ApertureShape::Image(distribution) => {
+                let (u, v, _) = distribution.sample(random.next_f64(), random.next_f64());
+                (2f64 * u - 1f64, 1f64 - 2f64 * v)
+            }
+        }
+    }
+}
+
+pub struct Lens {
+    pub aperture_radius: f64,
+    pub focus_distance: f64,
+    pub aperture_shape: ApertureShape
+}
+
+impl Lens {
+    pub fn from(aperture_radius: f64, focus_distance: f64, aperture_shape: ApertureShape) -> Option<Lens> {
+        if aperture_radius < 0f64 || focus_distance <= 0f64 {
+            return None;
+        }
+
+        Some(Lens { aperture_radius, focus_distance, aperture_shape })
+    }
+
+    pub fn sample_point(&self, random: &mut Random) -> (f64, f64) {
+        let (x, y) = self.aperture_shape.sample(random);
+        (x * self.aperture_radius, y * self.aperture_radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn polygonal_samples_stay_inside_the_polygon() {
+        let (blade_count, rotation) = (5, 0.3f64);
+        let aperture = ApertureShape::polygonal(blade_count, rotation).unwrap();
+        let corners: Vec<(f64, f64)> = (0..blade_count)
+            .map(|index| rotation + 2f64 * PI * index as f64 / blade_count as f64)
+            .map(|angle| (angle.cos(), angle.sin()))
+            .collect();
+        let mut random = Random::from_seed(1);
+        let mut corner_counts = vec![0; blade_count];
+
+        for _ in 0..10000 {
+            let (x, y) = aperture.sample(&mut random);
+
+            // the corners go around counterclockwise, so inside points are left of every edge
+            for index in 0..blade_count {
+                let ((x1, y1), (x2, y2)) = (corners[index], corners[(index + 1) % blade_count]);
+                assert!((x2 - x1) * (y - y1) - (y2 - y1) * (x - x1) >= -1e-12, "({}, {}) is outside edge {}", x, y, index);
+            }
+
+            let angle = (y.atan2(x) - rotation).rem_euclid(2f64 * PI);
+            corner_counts[(angle / (2f64 * PI) * blade_count as f64) as usize % blade_count] += 1;
+        }
+
+        // every triangle of the fan gets its share
+        assert!(corner_counts.iter().all(|count| (1800..2200).contains(count)), "{:?}", corner_counts);
+    }
+
+    #[test]
+    fn polygonal_aperture_needs_three_blades() {
+        assert!(ApertureShape::polygonal(2, 0f64).is_none());
+        assert!(ApertureShape::polygonal(3, 0f64).is_some());
+    }
+
+    #[test]
+    fn image_samples_only_land_on_lit_pixels() {
+        let mut mask = Screen::from(4, 4, Color::BLACK);
+        mask.paint_at(1, 0, Color::WHITE).unwrap();
+        mask.paint_at(3, 2, Color::from(0.5f64, 0.5f64, 0.5f64, 1f64)).unwrap();
+        let aperture = ApertureShape::from_image(&mask).unwrap();
+        let mut random = Random::from_seed(2);
+        let mut first_count = 0;
+
+        for _ in 0..10000 {
+            let (x, y) = aperture.sample(&mut random);
+            assert!((-1f64..=1f64).contains(&x) && (-1f64..=1f64).contains(&y));
+
+            // the top row of the mask is the top of the aperture
+            let pixel = (((x + 1f64) * 2f64) as usize, ((1f64 - y) * 2f64) as usize);
+            assert!(pixel == (1, 0) || pixel == (3, 2), "({}, {}) lands on {:?}", x, y, pixel);
+
+            if pixel == (1, 0) {
+                first_count += 1;
+            }
+        }
+
+        // the brighter pixel lets through twice as much light
+        assert!((6300..7000).contains(&first_count), "{}", first_count);
+    }
+
+    #[test]
+    fn black_image_is_not_an_aperture() {
+        assert!(ApertureShape::from_image(&Screen::from(4, 4, Color::BLACK)).is_none());
+    }
+
+    #[test]
+    fn lens_samples_scale_with_the_aperture_radius() {
+        let lens = Lens::from(0.25f64, 1f64, ApertureShape::Circular).unwrap();
+        let mut random = Random::from_seed(3);
+
+        for _ in 0..1000 {
+            let (x, y) = lens.sample_point(&mut random);
+            assert!(x * x + y * y <= 0.25f64 * 0.25f64 + 1e-12);
+        }
+    }
+}
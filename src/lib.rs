@@ -0,0 +1,53 @@
+pub mod color;
+pub mod surface;
+pub mod ray;
+pub mod vector;
+pub mod vector_macro;
+pub mod screen;
+pub mod camera;
+pub mod basis;
+pub mod sphere;
+pub mod multisurface;
+pub mod infinite_plane;
+pub mod light_source;
+pub mod directional_light;
+pub mod point_light;
+pub mod spot_light;
+pub mod area_light;
+pub mod environment;
+pub mod environment_light;
+pub mod preetham_sky;
+pub mod light_sampler;
+pub mod ambient_occlusion;
+pub mod ambient_occlusion_integrator;
+pub mod medium;
+pub mod homogeneous_medium;
+pub mod heterogeneous_medium;
+pub mod voxel_grid;
+pub mod photon_map;
+pub mod photon_mapping_integrator;
+pub mod light_ray;
+pub mod material;
+pub mod render_settings;
+pub mod random;
+pub mod sample_pattern;
+pub mod filter;
+pub mod film;
+pub mod sampling;
+pub mod scene;
+pub mod integrator;
+pub mod whitted_integrator;
+pub mod path_tracing_integrator;
+pub mod surface_interaction;
+pub mod debug_integrator;
+pub mod fresnel;
+pub mod distribution;
+pub mod lens;
+pub mod projection;
+pub mod perspective_projection;
+pub mod orthographic_projection;
+pub mod equirectangular_projection;
+pub mod cylindrical_projection;
+pub mod fisheye_projection;
+pub mod moving_surface;
+pub mod stereo_camera;
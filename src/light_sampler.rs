@@ -0,0 +1,267 @@
+use std::f64::consts::PI;
+
+use crate::distribution::Distribution1D;
+use crate::light_source::LightSource;
+use crate::random::Random;
+use crate::vector::Vector;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum LightSelection {
+    Uniform,
+    PowerProportional,
+    LightTree
+}
+
+enum LightTreeNodeKind {
+    Leaf(usize),
+    Interior(usize, usize)
+}
+
+struct LightTreeNode {
+    minimum: Vector,
+    maximum: Vector,
+    power: f64,
+    kind: LightTreeNodeKind
+}
+
+impl LightTreeNode {
+    // the irradiance of a single light at the node center that cannot be closer than the node's own extent
+    fn importance_at(&self, point: Vector) -> f64 {
+        let center = (self.minimum + self.maximum) * 0.5f64;
+        let half_diagonal_squared = (self.maximum - self.minimum).length_squared() * 0.25f64;
+
+        self.power / (4f64 * PI * (point - center).length_squared().max(half_diagonal_squared).max(1e-6f64))
+    }
+}
+
+enum Strategy {
+    Uniform,
+    PowerProportional(Distribution1D),
+    LightTree {
+        nodes: Vec<LightTreeNode>,
+        // lights without bounds compete with the whole tree using the irradiance they give every point
+        infinite_lights: Vec<(usize, f64)>
+    }
+}
+
+pub struct LightSampler {
+    strategy: Strategy,
+    light_count: usize,
+    pub light_sample_count: usize
+}
+
+impl LightSampler {
+    // must be rebuilt whenever the scene's light sources change
+    pub fn from(light_sources: &[&dyn LightSource], selection: LightSelection, light_sample_count: usize) -> Option<LightSampler> {
+        if light_sources.is_empty() || light_sample_count == 0 {
+            return None;
+        }
+
+        let powers: Vec<f64> = light_sources.iter().map(|light_source| light_source.get_power().max(0f64)).collect();
+
+        // lights without any power still have to be picked sometimes, they fall back to uniform selection
+        let strategy = match selection {
+            LightSelection::Uniform => Strategy::Uniform,
+            LightSelection::PowerProportional => match Distribution1D::from(&powers) {
+                Some(distribution) => Strategy::PowerProportional(distribution),
+                None => Strategy::Uniform
+            },
+            LightSelection::LightTree => LightSampler::build_tree_strategy(light_sources, &powers).unwrap_or(Strategy::Uniform)
+        };
+
+        Some(LightSampler { strategy, light_count: light_sources.len(), light_sample_count })
+    }
+
+    fn build_tree_strategy(light_sources: &[&dyn LightSource], powers: &[f64]) -> Option<Strategy> {
+        let (bounded, infinite_lights): (Vec<usize>, Vec<usize>) = (0..light_sources.len()).partition(|index| light_sources[*index].get_bounds().is_some());
+
+        let mut nodes = Vec::new();
+        let mut leaves: Vec<(usize, Vector, Vector)> = bounded.iter()
+            .filter_map(|index| light_sources[*index].get_bounds().map(|(minimum, maximum)| (*index, minimum, maximum)))
+            .collect();
+
+        let tree_power = if leaves.is_empty() { 0f64 } else { LightSampler::build_node(&mut nodes, &mut leaves, powers) };
+        let infinite_lights: Vec<(usize, f64)> = infinite_lights.iter().map(|index| (*index, powers[*index] / PI)).collect();
+
+        if tree_power + infinite_lights.iter().map(|(_, irradiance)| irradiance).sum::<f64>() <= 0f64 {
+            return None;
+        }
+
+        Some(Strategy::LightTree { nodes, infinite_lights })
+    }
+
+    // builds the subtree for the given leaves and returns its power, the subtree root ends up last in the node list
+    fn build_node(nodes: &mut Vec<LightTreeNode>, leaves: &mut [(usize, Vector, Vector)], powers: &[f64]) -> f64 {
+        let minimum = leaves.iter().fold(leaves[0].1, |minimum, (_, point, _)| Vector::from(minimum.x.min(point.x), minimum.y.min(point.y), minimum.z.min(point.z)));
+        let maximum = leaves.iter().fold(leaves[0].2, |maximum, (_, _, point)| Vector::from(maximum.x.max(point.x), maximum.y.max(point.y), maximum.z.max(point.z)));
+
+        if leaves.len() == 1 {
+            let power = powers[leaves[0].0];
+            nodes.push(LightTreeNode { minimum, maximum, power, kind: LightTreeNodeKind::Leaf(leaves[0].0) });
+            return power;
+        }
+
+        // lights are split at the median of their centers along the widest axis
+        let extent = maximum - minimum;
+        let axis_of = |point: Vector| if extent.x >= extent.y && extent.x >= extent.z { point.x } else if extent.y >= extent.z { point.y } else { point.z };
+        leaves.sort_by(|first, second| axis_of(first.1 + first.2).total_cmp(&axis_of(second.1 + second.2)));
+
+        let (left_leaves, right_leaves) = leaves.split_at_mut(leaves.len() / 2);
+        let left_power = LightSampler::build_node(nodes, left_leaves, powers);
+        let left = nodes.len() - 1;
+        let right_power = LightSampler::build_node(nodes, right_leaves, powers);
+        let right = nodes.len() - 1;
+
+        nodes.push(LightTreeNode { minimum, maximum, power: left_power + right_power, kind: LightTreeNodeKind::Interior(left, right) });
+        left_power + right_power
+    }
+
+    // returns the index of the picked light and the probability of picking it
+    pub fn sample(&self, point: Vector, random: &mut Random) -> (usize, f64) {
+        match &self.strategy {
+            Strategy::Uniform => (random.next_usize(self.light_count), 1f64 / self.light_count as f64),
+            Strategy::PowerProportional(distribution) => distribution.sample_index(random.next_f64()),
+            Strategy::LightTree { nodes, infinite_lights } => {
+                let tree_importance = nodes.last().map_or(0f64, |root| root.importance_at(point));
+                let total_importance = tree_importance + infinite_lights.iter().map(|(_, irradiance)| irradiance).sum::<f64>();
+
+                if total_importance <= 0f64 {
+                    return (0, 0f64);
+                }
+
+                let mut choice = random.next_f64() * total_importance;
+
+                for (light_index, irradiance) in infinite_lights {
+                    if choice < *irradiance {
+                        return (*light_index, irradiance / total_importance);
+                    }
+
+                    choice -= irradiance;
+                }
+
+                if nodes.is_empty() {
+                    return infinite_lights.last().map_or((0, 0f64), |(light_index, irradiance)| (*light_index, irradiance / total_importance));
+                }
+
+                let (mut node, mut probability) = (nodes.len() - 1, tree_importance / total_importance);
+
+                loop {
+                    match nodes[node].kind {
+                        LightTreeNodeKind::Leaf(light_index) => return (light_index, probability),
+                        LightTreeNodeKind::Interior(left, right) => {
+                            let (left_importance, right_importance) = (nodes[left].importance_at(point), nodes[right].importance_at(point));
+                            let left_probability = if left_importance + right_importance > 0f64 { left_importance / (left_importance + right_importance) } else { 0.5f64 };
+
+                            if random.next_f64() < left_probability {
+                                node = left;
+                                probability *= left_probability;
+                            } else {
+                                node = right;
+                                probability *= 1f64 - left_probability;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::directional_light::DirectionalLight;
+    use crate::point_light::PointLight;
+
+    const SAMPLE_COUNT: usize = 100000;
+
+    // the probability the sampler reports for each light, checked against how often the light actually comes up
+    fn probabilities(sampler: &LightSampler, light_count: usize, point: Vector) -> Vec<f64> {
+        let mut random = Random::from_seed(1);
+        let mut probabilities = vec![0f64; light_count];
+        let mut counts = vec![0usize; light_count];
+
+        for _ in 0..SAMPLE_COUNT {
+            let (index, probability) = sampler.sample(point, &mut random);
+
+            assert!(counts[index] == 0 || (probabilities[index] - probability).abs() < 1e-12, "light {} came up with different probabilities", index);
+            probabilities[index] = probability;
+            counts[index] += 1;
+        }
+
+        for index in 0..light_count {
+            let frequency = counts[index] as f64 / SAMPLE_COUNT as f64;
+            assert!((frequency - probabilities[index]).abs() < 0.01f64, "light {} came up {} of the time with probability {}", index, frequency, probabilities[index]);
+        }
+
+        probabilities
+    }
+
+    fn assert_sums_to_one(probabilities: &[f64]) {
+        assert!((probabilities.iter().sum::<f64>() - 1f64).abs() < 1e-9, "{:?}", probabilities);
+    }
+
+    #[test]
+    fn every_strategy_hands_out_probabilities_that_sum_to_one() {
+        let lights = [
+            PointLight::from(Vector::from(-4f64, 2f64, 0f64), 1f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::from(3f64, 1f64, 1f64), 4f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::from(0f64, 5f64, -2f64), 2f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::from(8f64, 0f64, 3f64), 0.5f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::from(1f64, -3f64, 6f64), 3f64, Color::WHITE).unwrap()
+        ];
+        let sun = DirectionalLight::from(Vector::from(0f64, -1f64, 0f64), Color::WHITE).unwrap();
+        let mut light_sources: Vec<&dyn LightSource> = lights.iter().map(|light| light as &dyn LightSource).collect();
+        light_sources.push(&sun);
+
+        for selection in [LightSelection::Uniform, LightSelection::PowerProportional, LightSelection::LightTree] {
+            let sampler = LightSampler::from(&light_sources, selection, 1).unwrap();
+
+            for point in [Vector::ZERO, Vector::from(3f64, 1.5f64, 1f64), Vector::from(-20f64, 10f64, 5f64)] {
+                assert_sums_to_one(&probabilities(&sampler, light_sources.len(), point));
+            }
+        }
+    }
+
+    #[test]
+    fn power_proportional_selection_follows_the_powers() {
+        let lights = [
+            PointLight::from(Vector::ZERO, 1f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::ZERO, 3f64, Color::WHITE).unwrap()
+        ];
+        let light_sources: Vec<&dyn LightSource> = lights.iter().map(|light| light as &dyn LightSource).collect();
+        let sampler = LightSampler::from(&light_sources, LightSelection::PowerProportional, 1).unwrap();
+
+        let probabilities = probabilities(&sampler, 2, Vector::ZERO);
+        assert!((probabilities[0] - 0.25f64).abs() < 1e-9 && (probabilities[1] - 0.75f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn light_tree_prefers_the_nearby_light() {
+        let lights = [
+            PointLight::from(Vector::from(-10f64, 0f64, 0f64), 1f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::from(10f64, 0f64, 0f64), 1f64, Color::WHITE).unwrap()
+        ];
+        let light_sources: Vec<&dyn LightSource> = lights.iter().map(|light| light as &dyn LightSource).collect();
+        let sampler = LightSampler::from(&light_sources, LightSelection::LightTree, 1).unwrap();
+
+        let probabilities = probabilities(&sampler, 2, Vector::from(9f64, 0f64, 0f64));
+        assert_sums_to_one(&probabilities);
+        assert!(probabilities[1] > 0.9f64, "{:?}", probabilities);
+    }
+
+    #[test]
+    fn powerless_lights_fall_back_to_uniform_selection() {
+        let lights = [
+            PointLight::from(Vector::ZERO, 0f64, Color::WHITE).unwrap(),
+            PointLight::from(Vector::from(1f64, 0f64, 0f64), 0f64, Color::WHITE).unwrap()
+        ];
+        let light_sources: Vec<&dyn LightSource> = lights.iter().map(|light| light as &dyn LightSource).collect();
+
+        for selection in [LightSelection::PowerProportional, LightSelection::LightTree] {
+            let sampler = LightSampler::from(&light_sources, selection, 1).unwrap();
+            assert_eq!(probabilities(&sampler, 2, Vector::ZERO), vec![0.5f64, 0.5f64]);
+        }
+    }
+}
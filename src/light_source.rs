@@ -1,7 +1,72 @@
+use std::f64::consts::PI;
+
+use crate::basis::Basis;
 use crate::color::Color;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
 use crate::vector::Vector;
 
-pub trait LightSource {
+pub struct LightSample {
+    pub direction: Vector,
+    pub distance: f64,
+    pub color: Color
+}
+
+pub trait LightSource: Sync {
     fn get_light_direction_from(&self, point: Vector) -> Vector;
-    fn get_light_color(&self) -> Color;
-}
\ No newline at end of file
+    fn get_light_distance_from(&self, point: Vector) -> f64;
+    fn get_light_color(&self, point: Vector) -> Color;
+
+    // lights that reach the whole scene report their power as the flux through a unit disk, the default guesses
+    // from the light one unit outside its bounds as if it shone equally in every direction
+    fn get_power(&self) -> f64 {
+        match self.get_bounds() {
+            Some((minimum, maximum)) => {
+                let distance = (maximum - minimum).length() * 0.5f64 + 1f64;
+                let probe = (minimum + maximum) * 0.5f64 + Vector::from(0f64, 0f64, distance);
+
+                4f64 * PI * distance * distance * self.get_light_color(probe).luminance()
+            },
+            None => PI * self.get_light_color(Vector::ZERO).luminance()
+        }
+    }
+
+    // lights without bounds are infinitely far away
+    fn get_bounds(&self) -> Option<(Vector, Vector)> {
+        None
+    }
+
+    fn get_shadow_sample_count(&self) -> usize {
+        1
+    }
+
+    // whether the point lies on an emissive surface this light already samples, so integrators do not count it twice
+    fn covers_point(&self, _point: Vector) -> bool {
+        false
+    }
+
+    // returns the photon's starting ray and the power it carries, the scene sphere tells lights without bounds where to aim,
+    // lights that cannot emit photons leave their light to the photon mapper's shadow rays
+    fn emit_photon(&self, _scene_center: Vector, _scene_radius: f64, _random: &mut Random) -> Option<(Ray, Color)> {
+        None
+    }
+
+    // lights without an extent are seen from the same direction by every shadow ray
+    fn sample_from(&self, point: Vector, _random: &mut Random) -> LightSample {
+        LightSample {
+            direction: self.get_light_direction_from(point),
+            distance: self.get_light_distance_from(point),
+            color: self.get_light_color(point)
+        }
+    }
+}
+
+// lights without bounds shoot their photons from a disk that covers the whole scene sphere
+pub fn emit_photon_towards_scene(direction: Vector, irradiance: Color, scene_center: Vector, scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+    let basis = Basis::from_w(direction)?;
+    let (disk_x, disk_y) = sampling::concentric_disk(random);
+    let origin = scene_center + basis.to_world(Vector::from(disk_x, disk_y, 0f64)) * scene_radius - basis.w * scene_radius;
+
+    Ray::from(origin, basis.w).map(|ray| (ray, irradiance * (PI * scene_radius * scene_radius)))
+}
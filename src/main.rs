@@ -1,49 +1,258 @@
 
-use std::fs::File;
-use std::io::Write;
-
-use crate::camera::Camera;
-use crate::color::Color;
-use crate::directional_light::DirectionalLight;
-use crate::infinite_plane::InfinitePlane;
-use crate::material::Material;
-use crate::multisurface::MultiSurface;
-
-
-use crate::screen::Screen;
-use crate::sphere::Sphere;
-use crate::vector::Vector;
-
-mod color;
-mod surface;
-mod ray;
-mod vector;
-mod vector_macro;
-mod screen;
-mod camera;
-mod basis;
-mod sphere;
-mod multisurface;
-mod infinite_plane;
-mod light_source;
-mod directional_light;
-mod light_ray;
-mod material;
+use std::env;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+use moon_raytracer::ambient_occlusion::AmbientOcclusion;
+use moon_raytracer::ambient_occlusion_integrator::AmbientOcclusionIntegrator;
+use moon_raytracer::area_light::{AreaLight, AreaLightShape};
+use moon_raytracer::camera::Camera;
+use moon_raytracer::color::Color;
+use moon_raytracer::cylindrical_projection::CylindricalProjection;
+use moon_raytracer::debug_integrator::{DebugIntegrator, DebugMode};
+use moon_raytracer::directional_light::DirectionalLight;
+use moon_raytracer::environment_light::EnvironmentLight;
+use moon_raytracer::equirectangular_projection::EquirectangularProjection;
+use moon_raytracer::film::Film;
+use moon_raytracer::heterogeneous_medium::HeterogeneousMedium;
+use moon_raytracer::homogeneous_medium::HomogeneousMedium;
+use moon_raytracer::fisheye_projection::FisheyeProjection;
+use moon_raytracer::filter::MitchellFilter;
+use moon_raytracer::infinite_plane::InfinitePlane;
+use moon_raytracer::integrator::Integrator;
+use moon_raytracer::lens::{ApertureShape, Lens};
+use moon_raytracer::light_sampler::{LightSampler, LightSelection};
+use moon_raytracer::light_source::LightSource;
+use moon_raytracer::material::Material;
+use moon_raytracer::medium::MediumRegion;
+use moon_raytracer::multisurface::MultiSurface;
+use moon_raytracer::orthographic_projection::OrthographicProjection;
+use moon_raytracer::path_tracing_integrator::PathTracingIntegrator;
+use moon_raytracer::photon_mapping_integrator::{PhotonMapSettings, PhotonMappingIntegrator};
+use moon_raytracer::perspective_projection::PerspectiveProjection;
+use moon_raytracer::point_light::PointLight;
+use moon_raytracer::preetham_sky::PreethamSky;
+use moon_raytracer::projection::Projection;
+use moon_raytracer::render_settings::{AdaptiveSampling, ProgressiveSettings, RenderSettings};
+use moon_raytracer::sample_pattern::SamplePattern;
+use moon_raytracer::scene::Scene;
+use moon_raytracer::screen::Screen;
+use moon_raytracer::sphere::Sphere;
+use moon_raytracer::spot_light::SpotLight;
+use moon_raytracer::stereo_camera::{StereoCamera, StereoLayout};
+use moon_raytracer::vector::Vector;
+use moon_raytracer::voxel_grid::VoxelGrid;
+use moon_raytracer::whitted_integrator::WhittedIntegrator;
 
 fn main() {
     let mut screen: Screen = Screen::from(1920, 1080, Color::BLACK);
-    let camera: Camera = Camera::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, 0f64, 1f64), 8f64, Vector::from(16f64, 9f64, 0f64)).unwrap();
+    let mut projection = PerspectiveProjection::from(8f64, Vector::from(16f64, 9f64, 0f64)).unwrap();
+
+    if env::args().any(|argument| argument == "--depth-of-field") {
+        projection.lens = Lens::from(0.8f64, 25f64, ApertureShape::polygonal(6, 0f64).unwrap());
+    }
+
+    let projection: Arc<dyn Projection> = match env::args().find_map(|argument| argument.strip_prefix("--projection=").map(str::to_string)).as_deref() {
+        Some("orthographic") => Arc::new(OrthographicProjection::from(Vector::from(64f64, 36f64, 0f64)).unwrap()),
+        Some("equirectangular") => Arc::new(EquirectangularProjection::from()),
+        Some("cylindrical") => Arc::new(CylindricalProjection::from(PI, PI / 2f64).unwrap()),
+        Some("fisheye") => Arc::new(FisheyeProjection::from(PI).unwrap()),
+        _ => Arc::new(projection)
+    };
+
+    let mut camera: Camera = Camera::with_projection(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, 0f64, 1f64), projection).unwrap();
+
+    let motion_blur = env::args().any(|argument| argument == "--motion-blur");
+    if motion_blur {
+        camera.shutter_close = 1f64;
+    }
 
     let sphere: Sphere = Sphere::from(Vector::from(0f64, -5f64, 30f64), 10f64, Material::from(Color::from(0f64, 1f64, 0f64, 1f64), 1f64, 1f64, 10f64, 1f64)).unwrap();
     let sphere3: Sphere = Sphere::from(Vector::from(-16f64, 0f64, 30f64), 6f64, Material::from(Color::from(1f64, 0.25f64, 0.125f64, 1f64), 1f64, 0.4f64, 2f64, 0.1f64)).unwrap();
-    let sphere2: Sphere = Sphere::from(Vector::from(0f64, 10f64, 30f64), 5f64, Material::from(Color::from(0.35f64, 0.15f64, 0.8f64, 1f64), 1f64, 1f64, 100f64, 1f64)).unwrap();
+    let sphere2_velocity = if motion_blur { Vector::from(4f64, 0f64, 0f64) } else { Vector::ZERO };
+    let sphere2: Sphere = Sphere::moving(Vector::from(0f64, 10f64, 30f64), sphere2_velocity, 5f64, Material::from(Color::from(0.35f64, 0.15f64, 0.8f64, 1f64), 1f64, 1f64, 100f64, 1f64)).unwrap();
     let infinite_plane: InfinitePlane = InfinitePlane::from(Vector::from(0f64, -4f64, 0f64), Vector::from(0f64, 1f64, 0f64), Material::from(Color::from(0.3f64, 0.3f64, 0.3f64, 1f64), 1f64, 1f64, 10f64, 1f64)).unwrap();
-    let world = MultiSurface::from(vec![&sphere2, &sphere, &sphere3, &infinite_plane]);
+    let glass_sphere: Sphere = Sphere::from(Vector::from(9f64, -1f64, 18f64), 3f64, Material::dielectric(Color::WHITE, 1.5f64, Color::from(0.08f64, 0.02f64, 0.08f64, 0f64)).unwrap()).unwrap();
+    let lamp_sphere: Sphere = Sphere::from(Vector::from(-7f64, -2.5f64, 16f64), 1.5f64, Material::emissive(Color::from(1f64, 0.6f64, 0.2f64, 1f64), 4f64).unwrap()).unwrap();
+
+    let emissive = env::args().any(|argument| argument == "--emissive");
+    let world = if emissive {
+        MultiSurface::from(vec![&sphere2, &sphere, &sphere3, &infinite_plane, &glass_sphere, &lamp_sphere])
+    } else {
+        MultiSurface::from(vec![&sphere2, &sphere, &sphere3, &infinite_plane, &glass_sphere])
+    };
+
+    let path_tracing = env::args().any(|argument| argument == "--path-tracing");
+    let photon_mapping = env::args().any(|argument| argument == "--photon-mapping");
+
+    // the path tracer and photon mapper divide lambertian reflection by pi, so the light is scaled up to keep the same exposure
+    let light_intensity = if path_tracing || photon_mapping { PI } else { 1f64 };
+    let directional_light = DirectionalLight::from(Vector::from(-1f64, -1f64, 0f64), Color::from(1f64, 1f64, 1f64, 1f64) * light_intensity).unwrap();
+
+    // a square panel where the directional light comes from, bright enough to match it at the spheres
+    let area_light_shape = AreaLightShape::rectangle(Vector::from(40f64, 40f64, 20f64), Vector::from(0f64, 0f64, 12f64), Vector::from(-8f64, 8f64, 0f64)).unwrap();
+    let area_light = AreaLight::from(area_light_shape, 34f64 * light_intensity, Color::WHITE, 8).unwrap();
+    let light: &dyn LightSource = if env::args().any(|argument| argument == "--area-light") { &area_light } else { &directional_light };
+
+    let directional_fill_light = DirectionalLight::from(Vector::from(1f64, -1f64, 0.5f64), Color::from(0.2f64, 0.15f64, 0.1f64, 1f64) * light_intensity).unwrap();
+    let point_light = PointLight::from(Vector::from(14f64, 4f64, 12f64), 60f64 * light_intensity, Color::from(1f64, 0.8f64, 0.5f64, 1f64)).unwrap();
+
+    // a window pane gobo, four bright squares split by dark bars
+    let is_bar = |coordinate: usize| !(4..60).contains(&coordinate) || (29..35).contains(&coordinate);
+    let mut window_gobo = Screen::from(64, 64, Color::WHITE);
+    for (x, y) in window_gobo.pixel_positions().filter(|&(x, y)| is_bar(x) || is_bar(y)).collect::<Vec<_>>() {
+        window_gobo.paint_at(x, y, Color::BLACK).unwrap();
+    }
+
+    let mut spot_light = SpotLight::from(Vector::from(-6f64, 20f64, 14f64), Vector::from(0.3f64, -1f64, 0.5f64), 400f64 * light_intensity, Color::from(1f64, 0.9f64, 0.7f64, 1f64), 0.25f64, 0.35f64).unwrap();
+    spot_light.gobo = Some(window_gobo);
+
+    let fill_light: &dyn LightSource = if env::args().any(|argument| argument == "--point-light") {
+        &point_light
+    } else if env::args().any(|argument| argument == "--spot-light") {
+        &spot_light
+    } else {
+        &directional_fill_light
+    };
+
+    let lamp_light = lamp_sphere.to_area_light(8).unwrap();
+    let mut scene = Scene::from(&world, vec![light, fill_light]);
+
+    if emissive {
+        scene.light_sources.push(&lamp_light);
+    }
+
+    let environment_path = env::args().find_map(|argument| argument.strip_prefix("--environment=").map(str::to_string));
+    let environment_light = environment_path.map(|path| EnvironmentLight::from(Screen::read_hdr(&path).unwrap(), 1f64, 4).unwrap());
+
+    let mut sky = PreethamSky::from(0.6f64, 1.2f64, 3f64, 0.02f64 * light_intensity).unwrap();
+    sky.shadow_sample_count = 4;
+    let sun = sky.get_sun_light().unwrap();
+
+    if env::args().any(|argument| argument == "--sky") {
+        scene.light_sources = vec![&sun, &sky];
+        scene.environment = Some(&sky);
+    }
+
+    if let Some(environment_light) = &environment_light {
+        scene.light_sources = vec![environment_light];
+        scene.environment = Some(environment_light);
+    }
+
+    // a grid of small colored lamps hovering over the floor, each hit point only looks at a couple of them
+    let light_selection = match env::args().find_map(|argument| argument.strip_prefix("--many-lights=").map(str::to_string)).as_deref() {
+        Some("uniform") => Some(LightSelection::Uniform),
+        Some("power") => Some(LightSelection::PowerProportional),
+        Some("tree") => Some(LightSelection::LightTree),
+        _ => None
+    };
+
+    let lamp_colors = [Color::from(1f64, 0.3f64, 0.2f64, 1f64), Color::from(0.3f64, 1f64, 0.4f64, 1f64), Color::from(0.3f64, 0.5f64, 1f64, 1f64), Color::from(1f64, 0.9f64, 0.4f64, 1f64)];
+    let grid_lamps: Vec<PointLight> = (0..200)
+        .filter_map(|index| PointLight::from(Vector::from(-40f64 + 4f64 * (index % 20) as f64, -3f64, 10f64 + 4f64 * (index / 20) as f64), 0.5f64 * light_intensity, lamp_colors[index % lamp_colors.len()]))
+        .collect();
+
+    if let Some(light_selection) = light_selection {
+        scene.light_sources.extend(grid_lamps.iter().map(|lamp| lamp as &dyn LightSource));
+        scene.light_sampler = LightSampler::from(&scene.light_sources, light_selection, 2);
+    }
+
+    // a thin haze over the whole scene and a ball of denser fog where the spot light shines through, --spot-light shows off the god rays
+    let haze = HomogeneousMedium::from(Color::from(0.001f64, 0.001f64, 0.001f64, 1f64), Color::from(0.012f64, 0.013f64, 0.015f64, 1f64), 0f64).unwrap();
+    let fog = HomogeneousMedium::from(Color::from(0.005f64, 0.005f64, 0.005f64, 1f64), Color::from(0.3f64, 0.3f64, 0.3f64, 1f64), 0.2f64).unwrap();
+    let fog_bank = Sphere::from(Vector::from(-1f64, 3f64, 22f64), 4f64, Material::from(Color::WHITE, 0f64, 0f64, 1f64, 0f64)).unwrap();
+
+    if env::args().any(|argument| argument == "--fog") {
+        scene.media.push(MediumRegion::global(&haze, Vector::from(0f64, 0f64, 25f64), 40f64).unwrap());
+        scene.media.push(MediumRegion::bounded(&fog, &fog_bank));
+    }
+
+    // a rising puff of smoke to the left of the green sphere, a density grid can also be loaded with --volume=<file.raw>
+    let grid_size = 32;
+    let puff_density = |x: f64, y: f64, z: f64| {
+        let swirl = 0.12f64 * (y * 9f64).sin() * (z * 7f64).cos();
+        let radius = ((x - 0.5f64 - swirl).powi(2) + (z - 0.5f64).powi(2)).sqrt();
+        let width = 0.15f64 + 0.2f64 * y;
+
+        (1f64 - radius / width).max(0f64) * (1f64 - y) * (1f64 + 0.5f64 * (x * 23f64).sin() * (y * 17f64).sin() * (z * 19f64).sin())
+    };
+
+    let grid_values = |value_at: &dyn Fn(f64, f64, f64) -> f64| (0..grid_size * grid_size * grid_size)
+        .map(|index| {
+            let position = |coordinate: usize| (coordinate as f64 + 0.5f64) / grid_size as f64;
+            value_at(position(index % grid_size), position(index / grid_size % grid_size), position(index / (grid_size * grid_size)))
+        })
+        .collect::<Vec<f64>>();
+
+    let volume_path = env::args().find_map(|argument| argument.strip_prefix("--volume=").map(str::to_string));
+    let smoke_density = match &volume_path {
+        Some(path) => VoxelGrid::read_raw(path).unwrap(),
+        None => VoxelGrid::from(grid_size, grid_size, grid_size, grid_values(&puff_density)).unwrap()
+    };
+
+    let mut smoke = HeterogeneousMedium::from(smoke_density, Vector::from(-13f64, -4f64, 16f64), Vector::from(-5f64, 10f64, 24f64), 3f64, Color::from(0.9f64, 0.9f64, 0.9f64, 1f64), 0.3f64).unwrap();
+
+    // the bottom of the puff burns, fading out as it rises
+    if env::args().any(|argument| argument == "--fire") {
+        smoke.emission = VoxelGrid::from(grid_size, grid_size, grid_size, grid_values(&|x, y, z| puff_density(x, y, z) * (1f64 - 2.5f64 * y).max(0f64).powi(2)));
+        smoke.emission_color = Color::from(4f64, 1.5f64, 0.3f64, 1f64) * light_intensity;
+    }
+
+    if volume_path.is_some() || env::args().any(|argument| argument == "--smoke" || argument == "--fire") {
+        scene.media.push(MediumRegion::global(&smoke, Vector::from(-9f64, 3f64, 20f64), 10f64).unwrap());
+    }
+
+    let integrator: Box<dyn Integrator> = if path_tracing {
+        Box::new(PathTracingIntegrator::from(16))
+    } else if photon_mapping {
+        let photon_map_settings = PhotonMapSettings::from(2_000_000, 200_000, Vector::from(0f64, 0f64, 25f64), 40f64).unwrap();
+        Box::new(PhotonMappingIntegrator::from(&scene, photon_map_settings, 8))
+    } else if env::args().any(|argument| argument == "--debug-normals") {
+        Box::new(DebugIntegrator::from(DebugMode::Normals))
+    } else if env::args().any(|argument| argument == "--ambient-occlusion") {
+        Box::new(AmbientOcclusionIntegrator::from(AmbientOcclusion::from(16, 10f64).unwrap()))
+    } else if env::args().any(|argument| argument == "--ambient-term") {
+        Box::new(WhittedIntegrator::with_ambient_occlusion(3, AmbientOcclusion::from(8, 10f64).unwrap(), Color::from(0.15f64, 0.15f64, 0.2f64, 0f64)))
+    } else {
+        Box::new(WhittedIntegrator::from(3))
+    };
+
+    let settings = RenderSettings {
+        samples_per_pixel: 4,
+        sample_pattern: SamplePattern::Stratified,
+        filter: Box::new(MitchellFilter::from(2f64, 1f64 / 3f64, 1f64 / 3f64).unwrap()),
+        adaptive_sampling: AdaptiveSampling::from(32, 0.05f64),
+        integrator,
+        ..RenderSettings::default()
+    };
+
+    let stereo_layout = match env::args().find_map(|argument| argument.strip_prefix("--stereo=").map(str::to_string)).as_deref() {
+        Some("side-by-side") => Some(StereoLayout::SideBySide),
+        Some("over-under") => Some(StereoLayout::OverUnder),
+        Some("anaglyph") => Some(StereoLayout::Anaglyph),
+        _ => None
+    };
+
+    if let Some(layout) = stereo_layout {
+        let stereo_camera = StereoCamera::from(&camera, 0.65f64, 30f64, layout).unwrap();
+        stereo_camera.print_to_screen(&scene, &mut screen, &settings);
+        screen.write_pbm("raytraced.pbm").unwrap();
+        return;
+    }
+
+    if env::args().any(|argument| argument == "--progressive") {
+        let progressive_settings = ProgressiveSettings::from(16, "raytraced.pbm", Some(4), Some(Duration::from_secs(10))).unwrap();
+        camera.print_progressively(&scene, &mut screen, &settings, &progressive_settings).unwrap();
+        return;
+    }
 
-    let light = DirectionalLight::from(Vector::from(-1f64, -1f64, 0f64), Color::from(1f64, 1f64, 1f64, 1f64)).unwrap();
+    let (width, height) = screen.resolution();
+    let mut film = Film::from(width, height);
 
-    camera.print_to_screen(&world, &light, &mut screen);
+    camera.render_to_film(&scene, &mut film, &settings);
+    film.develop_into(&mut screen);
 
-    let mut file = File::create("raytraced.pbm").unwrap();
-    file.write(screen.to_pbm().as_ref()).unwrap();
+    screen.write_pbm("raytraced.pbm").unwrap();
+    film.sample_count_screen().write_pbm("sample_counts.pbm").unwrap();
 }
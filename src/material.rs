@@ -1,4 +1,5 @@
 use crate::color::Color;
+use crate::fresnel::FresnelModel;
 
 #[derive(Copy, Clone, PartialEq)]
 pub struct Material {
@@ -6,7 +7,13 @@ pub struct Material {
     pub diffuse_coefficient: f64,
     pub specular_coefficient: f64,
     pub shininess_coefficient: f64,
-    pub mirror_reflection_coefficient: f64
+    pub mirror_reflection_coefficient: f64,
+    pub transmission_coefficient: f64,
+    pub refractive_index: f64,
+    pub absorption: Color,
+    pub fresnel_model: FresnelModel,
+    pub emission: Color,
+    pub emission_strength: f64
 }
 
 impl Material {
@@ -16,7 +23,55 @@ impl Material {
             diffuse_coefficient,
             specular_coefficient,
             shininess_coefficient,
-            mirror_reflection_coefficient
+            mirror_reflection_coefficient,
+            transmission_coefficient: 0f64,
+            refractive_index: 1f64,
+            absorption: Color::ZERO,
+            fresnel_model: FresnelModel::Exact,
+            emission: Color::BLACK,
+            emission_strength: 0f64
         }
     }
+
+    pub fn dielectric(color: Color, refractive_index: f64, absorption: Color) -> Option<Material> {
+        if refractive_index <= 0f64 {
+            return None;
+        }
+
+        Some(Material {
+            color,
+            diffuse_coefficient: 0f64,
+            specular_coefficient: 0f64,
+            shininess_coefficient: 1f64,
+            mirror_reflection_coefficient: 0f64,
+            transmission_coefficient: 1f64,
+            refractive_index,
+            absorption,
+            fresnel_model: FresnelModel::Exact,
+            emission: Color::BLACK,
+            emission_strength: 0f64
+        })
+    }
+
+    // a glowing surface that does not reflect any other light
+    pub fn emissive(emission: Color, emission_strength: f64) -> Option<Material> {
+        if emission_strength < 0f64 {
+            return None;
+        }
+
+        Some(Material { emission, emission_strength, ..Material::from(Color::BLACK, 0f64, 0f64, 1f64, 0f64) })
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission_strength > 0f64 && self.emission.max_component() > 0f64
+    }
+
+    pub fn emitted_radiance(&self) -> Color {
+        let emission = self.emission * self.emission_strength;
+        Color::from(emission.red, emission.green, emission.blue, 0f64)
+    }
+
+    pub fn transmittance_over(&self, distance: f64) -> Color {
+        Color::from((-self.absorption.red * distance).exp(), (-self.absorption.green * distance).exp(), (-self.absorption.blue * distance).exp(), 1f64)
+    }
 }
\ No newline at end of file
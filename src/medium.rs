@@ -0,0 +1,179 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+use crate::integrator::SURFACE_OFFSET;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::surface::{RayIntersectionResult, Surface};
+use crate::vector::Vector;
+
+const MAX_BOUNDARY_CROSSINGS: usize = 16;
+
+// cos_theta is the cosine between the direction light travelled in and the direction it leaves in
+pub fn henyey_greenstein(cos_theta: f64, asymmetry: f64) -> f64 {
+    let denominator = (1f64 + asymmetry * asymmetry - 2f64 * asymmetry * cos_theta).max(1e-12f64);
+    (1f64 - asymmetry * asymmetry) / (4f64 * PI * denominator * denominator.sqrt())
+}
+
+pub trait Medium: Sync {
+    // distances are measured along the ray, which is expected to be normalized
+    fn get_transmittance(&self, ray: Ray, start: f64, end: f64, random: &mut Random) -> Color;
+
+    // picks a point between start and end to scatter light from, weighted by the scattering coefficient
+    // and the transmittance from start to that point over the probability of picking it
+    fn sample_scattering(&self, ray: Ray, start: f64, end: f64, random: &mut Random) -> Option<(f64, Color)>;
+
+    // picks a point between start and end that gives off light, weighted the same way as scattering samples
+    fn sample_emission(&self, _ray: Ray, _start: f64, _end: f64, _random: &mut Random) -> Option<(f64, Color)> {
+        None
+    }
+
+    fn get_phase(&self, cos_theta: f64) -> f64;
+}
+
+pub enum MediumBoundary<'a> {
+    // an atmosphere filling a sphere around the scene, light from infinitely far away only travels through its radius
+    Sphere { center: Vector, radius: f64 },
+    // has to be a closed surface, it is only used to find where rays enter and leave the medium
+    Surface(&'a dyn Surface)
+}
+
+pub struct MediumRegion<'a> {
+    pub medium: &'a dyn Medium,
+    pub boundary: MediumBoundary<'a>
+}
+
+impl<'a> MediumRegion<'a> {
+    pub fn global(medium: &'a dyn Medium, center: Vector, radius: f64) -> Option<MediumRegion<'a>> {
+        if radius <= 0f64 {
+            return None;
+        }
+
+        Some(MediumRegion { medium, boundary: MediumBoundary::Sphere { center, radius } })
+    }
+
+    pub fn bounded(medium: &'a dyn Medium, boundary: &'a dyn Surface) -> MediumRegion<'a> {
+        MediumRegion { medium, boundary: MediumBoundary::Surface(boundary) }
+    }
+
+    pub fn get_intervals(&self, ray: Ray, max_distance: f64) -> Vec<(f64, f64)> {
+        let boundary = match self.boundary {
+            MediumBoundary::Surface(boundary) => boundary,
+            MediumBoundary::Sphere { center, radius } => {
+                let offset = ray.starting_point - center;
+                let (half_b, c) = (offset.dot(ray.direction), offset.length_squared() - radius * radius);
+                let discriminant = half_b * half_b - c;
+
+                if discriminant <= 0f64 {
+                    return Vec::new();
+                }
+
+                let (start, end) = ((-half_b - discriminant.sqrt()).max(0f64), (-half_b + discriminant.sqrt()).min(max_distance));
+                return if start < end { vec![(start, end)] } else { Vec::new() };
+            }
+        };
+
+        let mut intervals = Vec::new();
+        let (mut current_ray, mut travelled, mut entered_at) = (ray, 0f64, None);
+
+        for crossing in 0..MAX_BOUNDARY_CROSSINGS {
+            // an entry without a matching exit means the boundary is not closed, the open interval is dropped
+            let (t, front_face) = match boundary.intersect_with_ray(current_ray) {
+                RayIntersectionResult::Intersected { t, front_face, .. } => (t, front_face),
+                RayIntersectionResult::NoIntersection => break
+            };
+
+            // leaving through a back face before entering anywhere means the ray started inside
+            let inside_since = entered_at.or(if crossing == 0 { Some(0f64) } else { None });
+
+            if travelled + t >= max_distance {
+                if let (false, Some(start)) = (front_face, inside_since) {
+                    intervals.push((start, max_distance));
+                }
+
+                break;
+            }
+
+            if front_face {
+                entered_at = Some(travelled + t);
+            } else {
+                if let Some(start) = inside_since {
+                    intervals.push((start, travelled + t));
+                }
+
+                entered_at = None;
+            }
+
+            travelled += t + SURFACE_OFFSET;
+            current_ray = match current_ray.spawn(current_ray.point_at(t + SURFACE_OFFSET), current_ray.direction) {
+                Some(ray) => ray,
+                None => break
+            };
+        }
+
+        intervals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::homogeneous_medium::HomogeneousMedium;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+
+    fn intervals_along(origin: Vector, max_distance: f64) -> Vec<(f64, f64)> {
+        let medium = HomogeneousMedium::from(Color::BLACK, Color::WHITE, 0f64).unwrap();
+        let boundary = Sphere::from(Vector::from(0f64, 0f64, 10f64), 2f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let ray = Ray::from(origin, Vector::from(0f64, 0f64, 1f64)).unwrap();
+
+        MediumRegion::bounded(&medium, &boundary).get_intervals(ray, max_distance)
+    }
+
+    fn assert_intervals(intervals: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        assert_eq!(intervals.len(), expected.len(), "{:?}", intervals);
+
+        for ((start, end), (expected_start, expected_end)) in intervals.iter().zip(expected) {
+            assert!((start - expected_start).abs() < 1e-4 && (end - expected_end).abs() < 1e-4, "{:?}", intervals);
+        }
+    }
+
+    #[test]
+    fn ray_through_a_bounded_medium_enters_and_leaves() {
+        assert_intervals(intervals_along(Vector::ZERO, f64::INFINITY), &[(8f64, 12f64)]);
+    }
+
+    #[test]
+    fn ray_starting_inside_a_bounded_medium_leaves() {
+        assert_intervals(intervals_along(Vector::from(0f64, 0f64, 10f64), f64::INFINITY), &[(0f64, 2f64)]);
+    }
+
+    #[test]
+    fn ray_ending_inside_a_bounded_medium_is_cut_short() {
+        assert_intervals(intervals_along(Vector::ZERO, 10f64), &[(8f64, 10f64)]);
+    }
+
+    #[test]
+    fn ray_ending_before_a_bounded_medium_misses_it() {
+        assert_intervals(intervals_along(Vector::ZERO, 5f64), &[]);
+    }
+
+    #[test]
+    fn global_medium_only_fills_its_sphere() {
+        let medium = HomogeneousMedium::from(Color::BLACK, Color::WHITE, 0f64).unwrap();
+        let region = MediumRegion::global(&medium, Vector::from(0f64, 0f64, 10f64), 20f64).unwrap();
+        let ray = Ray::from(Vector::ZERO, Vector::from(0f64, 0f64, 1f64)).unwrap();
+
+        assert_intervals(region.get_intervals(ray, f64::INFINITY), &[(0f64, 30f64)]);
+        assert_intervals(region.get_intervals(ray, 12f64), &[(0f64, 12f64)]);
+
+        // a ray starting further along sees the same region, not a fresh stretch of medium
+        let later_ray = Ray::from(Vector::from(0f64, 0f64, 25f64), Vector::from(0f64, 0f64, 1f64)).unwrap();
+        assert_intervals(region.get_intervals(later_ray, f64::INFINITY), &[(0f64, 5f64)]);
+    }
+
+    #[test]
+    fn ray_starting_past_a_bounded_medium_misses_it() {
+        assert_intervals(intervals_along(Vector::from(0f64, 0f64, 20f64), f64::INFINITY), &[]);
+    }
+}
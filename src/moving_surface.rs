@@ -0,0 +1,131 @@
+use crate::ray::Ray;
+use crate::surface::{RayIntersectionResult, Surface};
+use crate::vector::Vector;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vector,
+    pub rotation_angle: f64
+}
+
+impl Keyframe {
+    pub fn from(time: f64, translation: Vector, rotation_angle: f64) -> Keyframe {
+        Keyframe { time, translation, rotation_angle }
+    }
+}
+
+pub struct MovingSurface<'a> {
+    surface: &'a dyn Surface,
+    rotation_axis: Vector,
+    start: Keyframe,
+    end: Keyframe
+}
+
+impl<'a> MovingSurface<'a> {
+    pub fn from(surface: &'a dyn Surface, rotation_axis: Vector, start: Keyframe, end: Keyframe) -> Option<MovingSurface<'a>> {
+        if end.time < start.time {
+            return None;
+        }
+
+        let rotation_axis = rotation_axis.normalized()?;
+        Some(MovingSurface { surface, rotation_axis, start, end })
+    }
+
+    // the surface is rotated around its own origin first and then moved by the translation
+    fn keyframe_at(&self, time: f64) -> (Vector, f64) {
+        let duration = self.end.time - self.start.time;
+        let progress = if duration > 0f64 { ((time - self.start.time) / duration).clamp(0f64, 1f64) } else { 0f64 };
+
+        let translation = self.start.translation + (self.end.translation - self.start.translation) * progress;
+        let rotation_angle = self.start.rotation_angle + (self.end.rotation_angle - self.start.rotation_angle) * progress;
+
+        (translation, rotation_angle)
+    }
+}
+
+impl<'a> Surface for MovingSurface<'a> {
+    fn intersect_with_ray(&self, ray: Ray) -> RayIntersectionResult {
+        let (translation, rotation_angle) = self.keyframe_at(ray.time);
+
+        let local_ray = Ray {
+            starting_point: (ray.starting_point - translation).rotated(-rotation_angle, self.rotation_axis),
+            direction: ray.direction.rotated(-rotation_angle, self.rotation_axis),
+            time: ray.time
+        };
+
+        match self.surface.intersect_with_ray(local_ray) {
+            RayIntersectionResult::Intersected { surface_material, surface_normal, t, front_face } => RayIntersectionResult::Intersected {
+                surface_material,
+                surface_normal: surface_normal.rotated(rotation_angle, self.rotation_axis),
+                t,
+                front_face
+            },
+            RayIntersectionResult::NoIntersection => RayIntersectionResult::NoIntersection
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+
+    fn hit_of(surface: &dyn Surface, ray: Ray) -> Option<(f64, Vector)> {
+        match surface.intersect_with_ray(ray) {
+            RayIntersectionResult::Intersected { t, surface_normal, .. } => Some((t, surface_normal)),
+            RayIntersectionResult::NoIntersection => None
+        }
+    }
+
+    #[test]
+    fn moving_sphere_is_hit_where_it_is_at_the_ray_time() {
+        let sphere = Sphere::from(Vector::ZERO, 1f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let moving = MovingSurface::from(&sphere, Vector::from(0f64, 1f64, 0f64), Keyframe::from(1f64, Vector::ZERO, 0f64), Keyframe::from(3f64, Vector::from(4f64, 0f64, 0f64), 0f64)).unwrap();
+
+        // a ray straight down through where the sphere is halfway through its move
+        let ray = Ray::from(Vector::from(2f64, 5f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap();
+
+        assert!(hit_of(&moving, ray.at_time(1f64)).is_none());
+        assert!(hit_of(&moving, ray.at_time(3f64)).is_none());
+
+        let (t, normal) = hit_of(&moving, ray.at_time(2f64)).unwrap();
+        assert!((t - 4f64).abs() < 1e-9);
+        assert!((normal - Vector::from(0f64, 1f64, 0f64)).length() < 1e-9);
+
+        // outside the keyframes the sphere rests at the nearest one
+        assert!(hit_of(&moving, Ray::from(Vector::from(4f64, 5f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap().at_time(10f64)).is_some());
+        assert!(hit_of(&moving, Ray::from(Vector::from(0f64, 5f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap().at_time(-10f64)).is_some());
+    }
+
+    #[test]
+    fn rotating_sphere_turns_around_the_axis_with_its_normals() {
+        let sphere = Sphere::from(Vector::from(2f64, 0f64, 0f64), 0.5f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let axis = Vector::from(0f64, 1f64, 0f64);
+        let moving = MovingSurface::from(&sphere, axis, Keyframe::from(0f64, Vector::ZERO, 0f64), Keyframe::from(1f64, Vector::ZERO, PI)).unwrap();
+
+        let center = Vector::from(2f64, 0f64, 0f64).rotated(PI / 2f64, axis);
+        let starting_point = center + Vector::from(0.3f64, 5f64, 0f64);
+        let ray = Ray::from(starting_point, Vector::from(0f64, -1f64, 0f64)).unwrap();
+
+        assert!(hit_of(&moving, ray).is_none());
+
+        // halfway through the turn the sphere sits a quarter turn around the axis
+        let (t, normal) = hit_of(&moving, ray.at_time(0.5f64)).unwrap();
+        let point = ray.point_at(t);
+        assert!(((point - center).length() - 0.5f64).abs() < 1e-9);
+        assert!((normal - (point - center) / 0.5f64).length() < 1e-9);
+    }
+
+    #[test]
+    fn keyframes_must_be_in_order() {
+        let sphere = Sphere::from(Vector::ZERO, 1f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+
+        assert!(MovingSurface::from(&sphere, Vector::from(0f64, 1f64, 0f64), Keyframe::from(1f64, Vector::ZERO, 0f64), Keyframe::from(0f64, Vector::ZERO, 0f64)).is_none());
+        assert!(MovingSurface::from(&sphere, Vector::ZERO, Keyframe::from(0f64, Vector::ZERO, 0f64), Keyframe::from(1f64, Vector::ZERO, 0f64)).is_none());
+    }
+}
@@ -9,7 +9,7 @@ pub struct MultiSurface<'a> {
 }
 
 impl<'a> MultiSurface<'a> {
-    pub fn from(surfaces: Vec<&dyn Surface>) -> MultiSurface {
+    pub fn from(surfaces: Vec<&'a dyn Surface>) -> MultiSurface<'a> {
         MultiSurface { surfaces }
     }
 }
@@ -19,12 +19,12 @@ impl<'a> Surface for MultiSurface<'a> {
         let intersection = self.surfaces.iter()
             .map(|surface| surface.intersect_with_ray(ray))
             .filter_map(|surface| match surface { 
-                RayIntersectionResult::Intersected { surface_normal, surface_material, t } if t.is_sign_positive() => Some((surface_normal, surface_material, t)),
+                RayIntersectionResult::Intersected { surface_normal, surface_material, t, front_face } if t.is_sign_positive() => Some((surface_normal, surface_material, t, front_face)),
                 _ => None
             })
-            .min_by(|(_, _, t1), (_, _, t2)| t1.partial_cmp(t2).unwrap());
+            .min_by(|(_, _, t1, _), (_, _, t2, _)| t1.partial_cmp(t2).unwrap());
         
-        let (normal, material, t) = match intersection { 
+        let (normal, material, t, front_face) = match intersection { 
             Some(intersection) => intersection,
             None => return RayIntersectionResult::NoIntersection
         };
@@ -32,7 +32,7 @@ impl<'a> Surface for MultiSurface<'a> {
         if t.is_infinite() {
             RayIntersectionResult::NoIntersection
         } else {
-            RayIntersectionResult::Intersected { surface_normal: normal, surface_material: material, t }
+            RayIntersectionResult::Intersected { surface_normal: normal, surface_material: material, t, front_face }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+use crate::basis::Basis;
+use crate::projection::{forward_of, right_of, Projection};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+pub struct OrthographicProjection {
+    view_extents: Vector
+}
+
+impl OrthographicProjection {
+    pub fn from(view_size: Vector) -> Option<OrthographicProjection> {
+        if view_size.x <= 0f64 || view_size.y <= 0f64 {
+            return None;
+        }
+
+        Some(OrthographicProjection { view_extents: view_size / 2f64 })
+    }
+}
+
+impl Projection for OrthographicProjection {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, _aspect_ratio: f64, _random: &mut Random) -> Option<Ray> {
+        let origin = position + right_of(basis) * self.view_extents.x * screen_x + basis.v * self.view_extents.y * screen_y;
+        Ray::from(origin, forward_of(basis))
+    }
+}
@@ -0,0 +1,151 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+use crate::integrator::Integrator;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
+use crate::scene::Scene;
+use crate::surface::RayIntersectionResult;
+use crate::surface_interaction::SurfaceInteraction;
+
+const RUSSIAN_ROULETTE_START_BOUNCE: usize = 3;
+
+pub struct PathTracingIntegrator {
+    max_bounces: usize
+}
+
+impl PathTracingIntegrator {
+    pub fn from(max_bounces: usize) -> PathTracingIntegrator {
+        PathTracingIntegrator { max_bounces }
+    }
+}
+
+impl Integrator for PathTracingIntegrator {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, random: &mut Random) -> Color {
+        let surface = scene.surface;
+        let mut radiance = Color::BLACK;
+        let mut throughput = Color::WHITE;
+        let mut ray = ray;
+        // lights are sampled directly at every diffuse hit, so only camera and mirror paths may see the environment or sampled emitters
+        let mut is_specular_path = true;
+
+        for bounce in 0..=self.max_bounces {
+            let (surface_material, surface_normal, t, front_face) = match surface.intersect_with_ray(ray) {
+                RayIntersectionResult::Intersected { surface_material, surface_normal, t, front_face } => (surface_material, surface_normal, t, front_face),
+                RayIntersectionResult::NoIntersection => {
+                    if is_specular_path {
+                        radiance = radiance + throughput * scene.apply_media(ray, f64::INFINITY, scene.get_background_color(ray.direction), random);
+                    } else {
+                        radiance = radiance + throughput * scene.get_medium_radiance(ray, f64::INFINITY, random);
+                    }
+
+                    break;
+                }
+            };
+
+            let hit = SurfaceInteraction::from(ray, surface_material, surface_normal, t, front_face);
+
+            radiance = radiance + throughput * hit.get_medium_radiance_to(scene, random);
+            throughput = throughput * hit.get_transmittance_to(scene, random);
+
+            // emissive surfaces hit after a diffuse bounce were already counted if their area light was sampled
+            if front_face && surface_material.is_emissive() && (is_specular_path || !scene.is_sampled_emitter(hit.position)) {
+                radiance = radiance + throughput * surface_material.emitted_radiance();
+            }
+
+            radiance = radiance + throughput * hit.sample_direct_light(scene, random);
+
+            let weights = hit.weights;
+            let scattering_weight = weights.total();
+            if scattering_weight <= 0f64 {
+                break;
+            }
+
+            let mirror_probability = weights.mirror / scattering_weight;
+            let transmission_probability = weights.transmission / scattering_weight;
+            let diffuse_probability = (weights.diffuse + weights.specular) / scattering_weight;
+            let lobe_choice = random.next_f64();
+            is_specular_path = lobe_choice < mirror_probability + transmission_probability;
+
+            let (origin, next_direction) = if lobe_choice < mirror_probability {
+                throughput = throughput * surface_material.color * (weights.mirror / mirror_probability);
+                hit.reflect()
+            } else if lobe_choice < mirror_probability + transmission_probability {
+                let (origin, direction, tint) = hit.sample_dielectric(random);
+                throughput = throughput * tint * (weights.transmission / transmission_probability);
+                (origin, direction)
+            } else {
+                let direction = sampling::cosine_weighted_hemisphere(hit.normal, random);
+                let cosine = hit.normal.dot(direction);
+
+                if cosine <= 0f64 {
+                    break;
+                }
+
+                // cosine weighted sampling has a pdf of cos / pi
+                throughput = throughput * hit.evaluate_brdf(direction) * (PI / diffuse_probability);
+                (hit.origin, direction)
+            };
+
+            if bounce >= RUSSIAN_ROULETTE_START_BOUNCE {
+                let survival_probability = throughput.max_component().min(0.95f64);
+
+                if random.next_f64() >= survival_probability {
+                    break;
+                }
+
+                throughput = throughput / survival_probability;
+            }
+
+            ray = match ray.spawn(origin, next_direction) {
+                Some(ray) => ray,
+                None => break
+            };
+        }
+
+        Color::from(radiance.red, radiance.green, radiance.blue, 1f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infinite_plane::InfinitePlane;
+    use crate::material::Material;
+    use crate::multisurface::MultiSurface;
+    use crate::sphere::Sphere;
+    use crate::vector::Vector;
+
+    fn floor_radiance(scene: &Scene) -> Color {
+        let integrator = PathTracingIntegrator::from(1);
+        let ray = Ray::from(Vector::from(0f64, 1f64, 0f64), Vector::from(0f64, -1f64, 0f64)).unwrap();
+        let mut random = Random::from_seed(7);
+
+        (0..2000).fold(Color::BLACK, |sum, _| sum + integrator.get_radiance(ray, scene, &mut random)) / 2000f64
+    }
+
+    #[test]
+    fn emitters_without_a_light_are_found_after_a_diffuse_bounce() {
+        let floor = InfinitePlane::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let ceiling = InfinitePlane::from(Vector::from(0f64, 2f64, 0f64), Vector::from(0f64, -1f64, 0f64), Material::emissive(Color::WHITE, 1f64).unwrap()).unwrap();
+        let world = MultiSurface::from(vec![&floor, &ceiling]);
+        let scene = Scene::from(&world, Vec::new());
+
+        // a white lambertian floor under a uniformly glowing ceiling reflects all of its radiance
+        assert!((floor_radiance(&scene).red - 1f64).abs() < 0.05f64, "{}", floor_radiance(&scene).red);
+    }
+
+    #[test]
+    fn emitters_sampled_as_area_lights_are_not_counted_twice() {
+        let floor = InfinitePlane::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let lamp = Sphere::from(Vector::from(0f64, 3f64, 0f64), 1f64, Material::emissive(Color::WHITE, 1f64).unwrap()).unwrap();
+        let lamp_light = lamp.to_area_light(4).unwrap();
+        let world = MultiSurface::from(vec![&floor, &lamp]);
+
+        let sampled_only = floor_radiance(&Scene::from(&floor, vec![&lamp_light]));
+        let sampled_and_hit = floor_radiance(&Scene::from(&world, vec![&lamp_light]));
+
+        assert!((sampled_and_hit.red - sampled_only.red).abs() < 0.1f64 * sampled_only.red, "{} against {}", sampled_and_hit.red, sampled_only.red);
+    }
+}
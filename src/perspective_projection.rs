@@ -0,0 +1,48 @@
+use crate::basis::Basis;
+use crate::lens::Lens;
+use crate::projection::{forward_of, right_of, Projection};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+pub struct PerspectiveProjection {
+    pub focal_length: f64,
+    pub sensor_extents: Vector,
+    pub lens: Option<Lens>
+}
+
+impl PerspectiveProjection {
+    pub fn from(focal_length: f64, sensor_size: Vector) -> Option<PerspectiveProjection> {
+        if focal_length <= 0f64 {
+            return None;
+        }
+
+        Some(PerspectiveProjection { focal_length, sensor_extents: sensor_size / 2f64, lens: None })
+    }
+
+    pub fn sensor_position_at(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64) -> Vector {
+        position + right_of(basis) * self.sensor_extents.x * screen_x + basis.v * self.sensor_extents.y * screen_y + forward_of(basis) * self.focal_length
+    }
+}
+
+impl Projection for PerspectiveProjection {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, _aspect_ratio: f64, random: &mut Random) -> Option<Ray> {
+        let pinhole_ray = Ray::from_points(position, self.sensor_position_at(basis, position, screen_x, screen_y))?;
+
+        let lens = match &self.lens {
+            Some(lens) if lens.aperture_radius > 0f64 => lens,
+            _ => return Some(pinhole_ray)
+        };
+
+        // every ray through the lens converges where the pinhole ray crosses the plane of focus
+        let focus_point = pinhole_ray.point_at(lens.focus_distance / pinhole_ray.direction.dot(forward_of(basis)));
+        let (lens_x, lens_y) = lens.sample_point(random);
+        let lens_point = position + right_of(basis) * lens_x + basis.v * lens_y;
+
+        Ray::from_points(lens_point, focus_point)
+    }
+
+    fn get_screen_x_from_eye(&self, eye_offset: f64, distance: f64) -> f64 {
+        -eye_offset * self.focal_length / (distance * self.sensor_extents.x)
+    }
+}
@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::color::Color;
+use crate::vector::Vector;
+
+#[derive(Copy, Clone)]
+pub struct Photon {
+    pub position: Vector,
+    pub direction: Vector,
+    pub power: Color
+}
+
+struct KdNode {
+    photon: Photon,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>
+}
+
+struct Neighbour {
+    squared_distance: f64,
+    index: usize
+}
+
+impl PartialEq for Neighbour {
+    fn eq(&self, other: &Self) -> bool {
+        self.squared_distance == other.squared_distance
+    }
+}
+
+impl Eq for Neighbour {}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.squared_distance.total_cmp(&other.squared_distance)
+    }
+}
+
+fn coordinate(vector: Vector, axis: usize) -> f64 {
+    match axis {
+        0 => vector.x,
+        1 => vector.y,
+        _ => vector.z
+    }
+}
+
+pub struct PhotonMap {
+    nodes: Vec<KdNode>,
+    root: Option<usize>
+}
+
+impl PhotonMap {
+    pub fn from(mut photons: Vec<Photon>) -> PhotonMap {
+        let mut nodes = Vec::with_capacity(photons.len());
+        let root = PhotonMap::build(&mut nodes, &mut photons);
+
+        PhotonMap { nodes, root }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // splits at the median along the axis where the photons spread the most
+    fn build(nodes: &mut Vec<KdNode>, photons: &mut [Photon]) -> Option<usize> {
+        if photons.is_empty() {
+            return None;
+        }
+
+        let (minimum, maximum) = photons.iter().fold((photons[0].position, photons[0].position), |(minimum, maximum), photon| {
+            let position = photon.position;
+            (Vector::from(minimum.x.min(position.x), minimum.y.min(position.y), minimum.z.min(position.z)), Vector::from(maximum.x.max(position.x), maximum.y.max(position.y), maximum.z.max(position.z)))
+        });
+
+        let extent = maximum - minimum;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+
+        let median = photons.len() / 2;
+        photons.select_nth_unstable_by(median, |first, second| coordinate(first.position, axis).total_cmp(&coordinate(second.position, axis)));
+
+        let (below, rest) = photons.split_at_mut(median);
+        let (photon, above) = (rest[0], &mut rest[1..]);
+
+        let index = nodes.len();
+        nodes.push(KdNode { photon, axis, left: None, right: None });
+
+        let left = PhotonMap::build(nodes, below);
+        let right = PhotonMap::build(nodes, above);
+        nodes[index].left = left;
+        nodes[index].right = right;
+
+        Some(index)
+    }
+
+    // returns up to count photons closest to the point within the radius, and the squared distance of the farthest one
+    pub fn find_nearest(&self, point: Vector, count: usize, max_radius: f64) -> (Vec<Photon>, f64) {
+        let mut neighbours = BinaryHeap::with_capacity(count + 1);
+        let mut search_radius_squared = max_radius * max_radius;
+
+        if let Some(root) = self.root {
+            self.search(root, point, count, &mut search_radius_squared, &mut neighbours);
+        }
+
+        let farthest_squared_distance = neighbours.peek().map_or(0f64, |neighbour: &Neighbour| neighbour.squared_distance);
+        let photons = neighbours.into_iter().map(|neighbour| self.nodes[neighbour.index].photon).collect();
+
+        (photons, farthest_squared_distance)
+    }
+
+    fn search(&self, index: usize, point: Vector, count: usize, search_radius_squared: &mut f64, neighbours: &mut BinaryHeap<Neighbour>) {
+        let node = &self.nodes[index];
+        let offset = coordinate(point, node.axis) - coordinate(node.photon.position, node.axis);
+        let (near, far) = if offset < 0f64 { (node.left, node.right) } else { (node.right, node.left) };
+
+        if let Some(near) = near {
+            self.search(near, point, count, search_radius_squared, neighbours);
+        }
+
+        let squared_distance = (node.photon.position - point).length_squared();
+        if squared_distance < *search_radius_squared {
+            neighbours.push(Neighbour { squared_distance, index });
+
+            // once the heap is full the search only needs to look as far as its farthest photon
+            if neighbours.len() > count {
+                neighbours.pop();
+            }
+
+            if neighbours.len() == count {
+                *search_radius_squared = neighbours.peek().map_or(*search_radius_squared, |neighbour| neighbour.squared_distance);
+            }
+        }
+
+        if let Some(far) = far {
+            if offset * offset < *search_radius_squared {
+                self.search(far, point, count, search_radius_squared, neighbours);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    fn random_photons(count: usize, random: &mut Random) -> Vec<Photon> {
+        (0..count)
+            .map(|_| Photon {
+                position: Vector::from(random.next_f64() * 10f64, random.next_f64() * 10f64, random.next_f64() * 10f64),
+                direction: Vector::from(0f64, -1f64, 0f64),
+                power: Color::WHITE
+            })
+            .collect()
+    }
+
+    fn sorted_distances(photons: &[Photon], point: Vector) -> Vec<f64> {
+        let mut distances: Vec<f64> = photons.iter().map(|photon| (photon.position - point).length_squared()).collect();
+        distances.sort_by(f64::total_cmp);
+        distances
+    }
+
+    #[test]
+    fn nearest_photons_match_a_brute_force_search() {
+        let mut random = Random::from_seed(3);
+        let photons = random_photons(2000, &mut random);
+        let photon_map = PhotonMap::from(photons.clone());
+
+        for _ in 0..50 {
+            let point = Vector::from(random.next_f64() * 10f64, random.next_f64() * 10f64, random.next_f64() * 10f64);
+            let (count, max_radius) = (1 + random.next_usize(40), 0.5f64 + random.next_f64() * 3f64);
+
+            let expected: Vec<f64> = sorted_distances(&photons, point).into_iter().filter(|distance| *distance < max_radius * max_radius).take(count).collect();
+            let (found, farthest_squared_distance) = photon_map.find_nearest(point, count, max_radius);
+
+            assert_eq!(sorted_distances(&found, point), expected);
+            assert_eq!(farthest_squared_distance, expected.last().copied().unwrap_or(0f64));
+        }
+    }
+
+    #[test]
+    fn empty_map_finds_nothing() {
+        let photon_map = PhotonMap::from(Vec::new());
+        let (found, farthest_squared_distance) = photon_map.find_nearest(Vector::ZERO, 8, 1f64);
+
+        assert!(photon_map.is_empty());
+        assert!(found.is_empty());
+        assert_eq!(farthest_squared_distance, 0f64);
+    }
+
+    #[test]
+    fn every_photon_ends_up_in_the_tree() {
+        let photons = random_photons(1000, &mut Random::from_seed(5));
+        let photon_map = PhotonMap::from(photons);
+        let (found, _) = photon_map.find_nearest(Vector::from(5f64, 5f64, 5f64), 2000, 100f64);
+
+        assert_eq!(photon_map.len(), 1000);
+        assert_eq!(found.len(), 1000);
+    }
+}
@@ -0,0 +1,253 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+use crate::distribution::Distribution1D;
+use crate::integrator::Integrator;
+use crate::photon_map::{Photon, PhotonMap};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
+use crate::scene::Scene;
+use crate::surface::RayIntersectionResult;
+use crate::surface_interaction::SurfaceInteraction;
+use crate::vector::Vector;
+
+const PHOTON_SEED: u64 = 0x5eed;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PhotonMapSettings {
+    pub caustic_photon_count: usize,
+    pub global_photon_count: usize,
+    pub max_photon_bounces: usize,
+    pub gather_count: usize,
+    pub gather_radius: f64,
+    // lights without bounds aim their photons at this sphere, it should hold everything worth lighting
+    pub scene_center: Vector,
+    pub scene_radius: f64
+}
+
+impl PhotonMapSettings {
+    pub fn from(caustic_photon_count: usize, global_photon_count: usize, scene_center: Vector, scene_radius: f64) -> Option<PhotonMapSettings> {
+        if scene_radius <= 0f64 {
+            return None;
+        }
+
+        Some(PhotonMapSettings {
+            caustic_photon_count,
+            global_photon_count,
+            max_photon_bounces: 8,
+            gather_count: 64,
+            gather_radius: 1f64,
+            scene_center,
+            scene_radius
+        })
+    }
+}
+
+pub struct PhotonMappingIntegrator {
+    caustic_map: PhotonMap,
+    global_map: PhotonMap,
+    settings: PhotonMapSettings,
+    max_depth: usize
+}
+
+impl PhotonMappingIntegrator {
+    // photons are shot once here, every pixel then reads from the same maps
+    pub fn from(scene: &Scene, settings: PhotonMapSettings, max_depth: usize) -> PhotonMappingIntegrator {
+        let mut random = Random::from_seed(PHOTON_SEED);
+        let caustic_map = PhotonMap::from(PhotonMappingIntegrator::trace_photons(scene, &settings, settings.caustic_photon_count, true, &mut random));
+        let global_map = PhotonMap::from(PhotonMappingIntegrator::trace_photons(scene, &settings, settings.global_photon_count, false, &mut random));
+
+        PhotonMappingIntegrator { caustic_map, global_map, settings, max_depth }
+    }
+
+    // caustic photons only went through mirrors and glass before landing, global photons bounced off a diffuse surface at least once
+    fn trace_photons(scene: &Scene, settings: &PhotonMapSettings, photon_count: usize, is_caustic: bool, random: &mut Random) -> Vec<Photon> {
+        let mut photons = Vec::new();
+
+        if photon_count == 0 || scene.light_sources.is_empty() {
+            return photons;
+        }
+
+        let powers: Vec<f64> = scene.light_sources.iter().map(|light_source| light_source.get_power().max(0f64)).collect();
+        let light_distribution = match Distribution1D::from(&powers) {
+            Some(distribution) => distribution,
+            None => return photons
+        };
+
+        for _ in 0..photon_count {
+            let (light_index, light_probability) = light_distribution.sample_index(random.next_f64());
+            let (mut ray, power) = match scene.light_sources[light_index].emit_photon(settings.scene_center, settings.scene_radius, random) {
+                Some(photon) => photon,
+                None => continue
+            };
+
+            let mut power = power / (light_probability * photon_count as f64);
+            let (mut has_diffuse_bounce, mut has_specular_bounce) = (false, false);
+
+            for _ in 0..settings.max_photon_bounces {
+                let (surface_material, surface_normal, t, front_face) = match scene.surface.intersect_with_ray(ray) {
+                    RayIntersectionResult::Intersected { surface_material, surface_normal, t, front_face } => (surface_material, surface_normal, t, front_face),
+                    RayIntersectionResult::NoIntersection => break
+                };
+
+                let hit = SurfaceInteraction::from(ray, surface_material, surface_normal, t, front_face);
+
+                // photons are only dimmed by the media, the light they scatter is gathered from the camera side
+                power = power * hit.get_transmittance_to(scene, random);
+
+                let weights = hit.weights;
+                let diffuse_weight = weights.diffuse + weights.specular;
+
+                let is_stored = if is_caustic { has_specular_bounce && !has_diffuse_bounce } else { has_diffuse_bounce };
+                if diffuse_weight > 0f64 && is_stored {
+                    photons.push(Photon { position: hit.position, direction: ray.direction, power });
+                }
+
+                // the lobe is picked with its weight as probability, so the weight cancels out of the power
+                let lobe_choice = random.next_f64();
+
+                let (origin, next_direction) = if lobe_choice < diffuse_weight {
+                    if is_caustic {
+                        break;
+                    }
+
+                    has_diffuse_bounce = true;
+                    power = power * surface_material.color;
+                    (hit.origin, sampling::cosine_weighted_hemisphere(hit.normal, random))
+                } else if lobe_choice < diffuse_weight + weights.mirror {
+                    has_specular_bounce = true;
+                    power = power * surface_material.color;
+                    hit.reflect()
+                } else if lobe_choice < diffuse_weight + weights.mirror + weights.transmission {
+                    has_specular_bounce = true;
+                    let (origin, direction, tint) = hit.sample_dielectric(random);
+                    power = power * tint;
+                    (origin, direction)
+                } else {
+                    break;
+                };
+
+                ray = match ray.spawn(origin, next_direction) {
+                    Some(ray) => ray,
+                    None => break
+                };
+            }
+        }
+
+        photons
+    }
+
+    // the power of the nearby photons spread over the disk they were found in
+    fn estimate_radiance(&self, photon_map: &PhotonMap, point: Vector, normal: Vector, diffuse_brdf: Color) -> Color {
+        if photon_map.is_empty() {
+            return Color::BLACK;
+        }
+
+        let (photons, farthest_squared_distance) = photon_map.find_nearest(point, self.settings.gather_count, self.settings.gather_radius);
+
+        // a handful of photons says little about their spacing, so sparse regions use the whole gather disk
+        let squared_radius = if photons.len() < self.settings.gather_count { self.settings.gather_radius.powi(2) } else { farthest_squared_distance };
+
+        if photons.is_empty() || squared_radius <= 0f64 {
+            return Color::BLACK;
+        }
+
+        let flux = photons.iter()
+            .filter(|photon| photon.direction.dot(normal) < 0f64)
+            .fold(Color::BLACK, |flux, photon| flux + photon.power);
+
+        diffuse_brdf * flux / (PI * squared_radius)
+    }
+}
+
+impl Integrator for PhotonMappingIntegrator {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, random: &mut Random) -> Color {
+        let surface = scene.surface;
+        let mut radiance = Color::BLACK;
+        let mut throughput = Color::WHITE;
+        let mut ray = ray;
+
+        // only mirror and glass bounces are followed, every diffuse surface on the way reads its light from the maps
+        for _ in 0..=self.max_depth {
+            let (surface_material, surface_normal, t, front_face) = match surface.intersect_with_ray(ray) {
+                RayIntersectionResult::Intersected { surface_material, surface_normal, t, front_face } => (surface_material, surface_normal, t, front_face),
+                RayIntersectionResult::NoIntersection => {
+                    radiance = radiance + throughput * scene.apply_media(ray, f64::INFINITY, scene.get_background_color(ray.direction), random);
+                    break;
+                }
+            };
+
+            let hit = SurfaceInteraction::from(ray, surface_material, surface_normal, t, front_face);
+
+            radiance = radiance + throughput * hit.get_medium_radiance_to(scene, random);
+            throughput = throughput * hit.get_transmittance_to(scene, random);
+
+            if front_face {
+                radiance = radiance + throughput * surface_material.emitted_radiance();
+            }
+
+            let weights = hit.weights;
+
+            if weights.diffuse + weights.specular > 0f64 {
+                radiance = radiance + throughput * hit.sample_direct_light(scene, random);
+
+                // photons stored for the blinn-phong lobe are read back as if it was diffuse
+                let diffuse_brdf = surface_material.color * ((weights.diffuse + weights.specular) / PI);
+
+                radiance = radiance + throughput * (self.estimate_radiance(&self.caustic_map, hit.position, hit.normal, diffuse_brdf) + self.estimate_radiance(&self.global_map, hit.position, hit.normal, diffuse_brdf));
+            }
+
+            let lobe_choice = random.next_f64();
+
+            let (origin, next_direction) = if lobe_choice < weights.mirror {
+                throughput = throughput * surface_material.color;
+                hit.reflect()
+            } else if lobe_choice < weights.mirror + weights.transmission {
+                let (origin, direction, tint) = hit.sample_dielectric(random);
+                throughput = throughput * tint;
+                (origin, direction)
+            } else {
+                break;
+            };
+
+            ray = match ray.spawn(origin, next_direction) {
+                Some(ray) => ray,
+                None => break
+            };
+        }
+
+        Color::from(radiance.red, radiance.green, radiance.blue, 1f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directional_light::DirectionalLight;
+    use crate::infinite_plane::InfinitePlane;
+    use crate::material::Material;
+    use crate::multisurface::MultiSurface;
+    use crate::sphere::Sphere;
+
+    #[test]
+    fn caustic_photons_leave_glass_through_its_back_face() {
+        // red is absorbed inside the glass, photons only pick that up when they are seen leaving through the back face
+        let glass = Sphere::from(Vector::from(0f64, 1.5f64, 0f64), 1f64, Material::dielectric(Color::WHITE, 1.5f64, Color::from(1f64, 0f64, 0f64, 0f64)).unwrap()).unwrap();
+        let floor = InfinitePlane::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap();
+        let world = MultiSurface::from(vec![&glass, &floor]);
+        let light = DirectionalLight::from(Vector::from(0f64, -1f64, 0f64), Color::WHITE).unwrap();
+        let scene = Scene::from(&world, vec![&light]);
+
+        let settings = PhotonMapSettings::from(20_000, 0, Vector::from(0f64, 1.5f64, 0f64), 1f64).unwrap();
+        let photons = PhotonMappingIntegrator::trace_photons(&scene, &settings, settings.caustic_photon_count, true, &mut Random::from_seed(1));
+
+        let under_sphere: Vec<&Photon> = photons.iter().filter(|photon| photon.position.y.abs() < 1e-3f64 && Vector::from(photon.position.x, 0f64, photon.position.z).length() < 0.3f64).collect();
+        let power = under_sphere.iter().fold(Color::BLACK, |power, photon| power + photon.power);
+
+        // close to the axis photons travel about two units through the glass, so red should be down to about e^-2
+        assert!(under_sphere.len() > 1000, "only {} photons under the sphere", under_sphere.len());
+        assert!(power.red < 0.3f64 * power.green, "red {} against green {}", power.red, power.green);
+        assert!(power.green > 0f64);
+    }
+}
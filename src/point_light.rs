@@ -0,0 +1,102 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+
+use crate::light_source::LightSource;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
+use crate::vector::Vector;
+
+pub struct PointLight {
+    pub position: Vector,
+    pub intensity: f64,
+    pub color: Color
+}
+
+impl PointLight {
+    pub fn from(position: Vector, intensity: f64, color: Color) -> Option<PointLight> {
+        if intensity < 0f64 {
+            return None;
+        }
+
+        Some(PointLight { position, intensity, color })
+    }
+}
+
+impl LightSource for PointLight {
+    fn get_light_direction_from(&self, point: Vector) -> Vector {
+        (point - self.position).normalized_or_zero()
+    }
+
+    fn get_light_distance_from(&self, point: Vector) -> f64 {
+        (point - self.position).length()
+    }
+
+    fn get_light_color(&self, point: Vector) -> Color {
+        let squared_distance = (point - self.position).length_squared();
+
+        if squared_distance <= 0f64 {
+            return Color::ZERO;
+        }
+
+        self.color * (self.intensity / squared_distance)
+    }
+
+    fn get_power(&self) -> f64 {
+        4f64 * PI * self.intensity * self.color.luminance()
+    }
+
+    fn get_bounds(&self) -> Option<(Vector, Vector)> {
+        Some((self.position, self.position))
+    }
+
+    fn emit_photon(&self, _scene_center: Vector, _scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+        Ray::from(self.position, sampling::uniform_sphere(random)).map(|ray| (ray, self.color * (4f64 * PI * self.intensity)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+    use crate::scene::Scene;
+    use crate::sphere::Sphere;
+
+    #[test]
+    fn point_light_falls_off_with_the_square_of_the_distance() {
+        let light = PointLight::from(Vector::ZERO, 8f64, Color::from(1f64, 0.5f64, 0.25f64, 1f64)).unwrap();
+
+        for distance in [1f64, 2f64, 4f64, 0.5f64] {
+            let color = light.get_light_color(Vector::from(0f64, distance, 0f64));
+            assert!((color.red - 8f64 / (distance * distance)).abs() < 1e-12 && (color.blue - 2f64 / (distance * distance)).abs() < 1e-12);
+        }
+
+        assert_eq!(light.get_light_color(Vector::ZERO).max_component(), 0f64);
+    }
+
+    #[test]
+    fn point_light_is_seen_from_its_position() {
+        let light = PointLight::from(Vector::from(1f64, 2f64, 3f64), 1f64, Color::WHITE).unwrap();
+        let point = Vector::from(1f64, 2f64, -1f64);
+
+        assert!((light.get_light_direction_from(point) - Vector::from(0f64, 0f64, -1f64)).length() < 1e-12);
+        assert_eq!(light.get_light_distance_from(point), 4f64);
+        assert!(PointLight::from(Vector::ZERO, -1f64, Color::WHITE).is_none());
+    }
+
+    #[test]
+    fn shadow_rays_stop_at_the_point_light() {
+        let light = PointLight::from(Vector::from(0f64, 0f64, 5f64), 1f64, Color::WHITE).unwrap();
+        let point = Vector::ZERO;
+        let sample = light.sample_from(point, &mut Random::from_seed(1));
+        let shadow_ray = Ray::from(point, -sample.direction).unwrap();
+
+        let material = Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64);
+        let behind = Sphere::from(Vector::from(0f64, 0f64, 8f64), 1f64, material).unwrap();
+        let between = Sphere::from(Vector::from(0f64, 0f64, 2.5f64), 1f64, material).unwrap();
+
+        assert!(Scene::from(&behind, vec![&light]).is_unoccluded(shadow_ray, sample.distance));
+        assert!(!Scene::from(&between, vec![&light]).is_unoccluded(shadow_ray, sample.distance));
+    }
+}
@@ -0,0 +1,164 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::color::Color;
+use crate::directional_light::DirectionalLight;
+use crate::environment::{self, Environment, UP};
+use crate::light_source::{LightSample, LightSource};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::sampling;
+use crate::vector::Vector;
+
+// illuminance of the sun above the atmosphere in kilolux, the sky radiance is in kilocandela per square meter
+const SOLAR_ILLUMINANCE: f64 = 128f64;
+
+// red, green and blue wavelengths in micrometers, used for the sun's atmospheric extinction
+const WAVELENGTHS: [f64; 3] = [0.68f64, 0.55f64, 0.44f64];
+
+#[derive(Copy, Clone)]
+struct PerezCoefficients {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64
+}
+
+impl PerezCoefficients {
+    fn evaluate(&self, cos_theta: f64, gamma: f64) -> f64 {
+        (1f64 + self.a * (self.b / cos_theta.max(1e-3f64)).exp()) * (1f64 + self.c * (self.d * gamma).exp() + self.e * gamma.cos().powi(2))
+    }
+}
+
+pub struct PreethamSky {
+    sun_direction: Vector,
+    sun_zenith_angle: f64,
+    turbidity: f64,
+    zenith_values: [f64; 3],
+    coefficients: [PerezCoefficients; 3],
+    pub intensity: f64,
+    pub shadow_sample_count: usize
+}
+
+impl PreethamSky {
+    // elevation is measured from the horizon, azimuth from +z towards +x
+    pub fn from(sun_elevation: f64, sun_azimuth: f64, turbidity: f64, intensity: f64) -> Option<PreethamSky> {
+        if !(1f64..=20f64).contains(&turbidity) || !(-FRAC_PI_2..=FRAC_PI_2).contains(&sun_elevation) || intensity < 0f64 {
+            return None;
+        }
+
+        let sun_direction = Vector::from(sun_elevation.cos() * sun_azimuth.sin(), sun_elevation.sin(), sun_elevation.cos() * sun_azimuth.cos());
+
+        // the model is only fitted for a sun above the horizon
+        let theta = (FRAC_PI_2 - sun_elevation).min(FRAC_PI_2);
+        let t = turbidity;
+
+        let chi = (4f64 / 9f64 - t / 120f64) * (PI - 2f64 * theta);
+        let zenith_luminance = ((4.0453f64 * t - 4.9710f64) * chi.tan() - 0.2155f64 * t + 2.4192f64).max(0f64);
+
+        let (theta2, theta3) = (theta * theta, theta * theta * theta);
+        let zenith_x = t * t * (0.00166f64 * theta3 - 0.00375f64 * theta2 + 0.00209f64 * theta)
+            + t * (-0.02903f64 * theta3 + 0.06377f64 * theta2 - 0.03202f64 * theta + 0.00394f64)
+            + (0.11693f64 * theta3 - 0.21196f64 * theta2 + 0.06052f64 * theta + 0.25886f64);
+        let zenith_y = t * t * (0.00275f64 * theta3 - 0.00610f64 * theta2 + 0.00317f64 * theta)
+            + t * (-0.04214f64 * theta3 + 0.08970f64 * theta2 - 0.04153f64 * theta + 0.00516f64)
+            + (0.15346f64 * theta3 - 0.26756f64 * theta2 + 0.06670f64 * theta + 0.26688f64);
+
+        let coefficients = [
+            PerezCoefficients { a: 0.1787f64 * t - 1.4630f64, b: -0.3554f64 * t + 0.4275f64, c: -0.0227f64 * t + 5.3251f64, d: 0.1206f64 * t - 2.5771f64, e: -0.0670f64 * t + 0.3703f64 },
+            PerezCoefficients { a: -0.0193f64 * t - 0.2592f64, b: -0.0665f64 * t + 0.0008f64, c: -0.0004f64 * t + 0.2125f64, d: -0.0641f64 * t - 0.8989f64, e: -0.0033f64 * t + 0.0452f64 },
+            PerezCoefficients { a: -0.0167f64 * t - 0.2608f64, b: -0.0950f64 * t + 0.0092f64, c: -0.0079f64 * t + 0.2102f64, d: -0.0441f64 * t - 1.6537f64, e: -0.0109f64 * t + 0.0529f64 }
+        ];
+
+        Some(PreethamSky {
+            sun_direction,
+            sun_zenith_angle: theta,
+            turbidity,
+            zenith_values: [zenith_luminance, zenith_x, zenith_y],
+            coefficients,
+            intensity,
+            shadow_sample_count: 1
+        })
+    }
+
+    // the sun light is dimmed and reddened by the same atmosphere that produces the sky
+    pub fn get_sun_light(&self) -> Option<DirectionalLight> {
+        if self.sun_direction.y <= 0f64 {
+            return DirectionalLight::from(-self.sun_direction, Color::BLACK);
+        }
+
+        let zenith_degrees = self.sun_zenith_angle.to_degrees();
+        let air_mass = 1f64 / (self.sun_zenith_angle.cos() + 0.15f64 * (93.885f64 - zenith_degrees).powf(-1.253f64));
+        let angstrom_beta = 0.04608365822050f64 * self.turbidity - 0.04586025928522f64;
+
+        let transmittance = WAVELENGTHS.map(|wavelength| {
+            let rayleigh_depth = 0.008735f64 * wavelength.powf(-4.08f64);
+            let aerosol_depth = angstrom_beta * wavelength.powf(-1.3f64);
+            (-air_mass * (rayleigh_depth + aerosol_depth)).exp()
+        });
+
+        let scale = SOLAR_ILLUMINANCE * self.intensity;
+        DirectionalLight::from(-self.sun_direction, Color::from(transmittance[0] * scale, transmittance[1] * scale, transmittance[2] * scale, 1f64))
+    }
+
+    fn get_sky_radiance(&self, direction: Vector) -> Color {
+        if direction.y <= 0f64 {
+            return Color::BLACK;
+        }
+
+        let cos_theta = direction.y;
+        let gamma = direction.dot(self.sun_direction).clamp(-1f64, 1f64).acos();
+
+        let [luminance, x, y] = [0, 1, 2].map(|index| {
+            let coefficients = self.coefficients[index];
+            self.zenith_values[index] * coefficients.evaluate(cos_theta, gamma) / coefficients.evaluate(1f64, self.sun_zenith_angle)
+        });
+
+        if y <= 0f64 {
+            return Color::BLACK;
+        }
+
+        let (tristimulus_x, tristimulus_y, tristimulus_z) = (x / y * luminance, luminance, (1f64 - x - y) / y * luminance);
+        let red = 3.2406f64 * tristimulus_x - 1.5372f64 * tristimulus_y - 0.4986f64 * tristimulus_z;
+        let green = -0.9689f64 * tristimulus_x + 1.8758f64 * tristimulus_y + 0.0415f64 * tristimulus_z;
+        let blue = 0.0557f64 * tristimulus_x - 0.2040f64 * tristimulus_y + 1.0570f64 * tristimulus_z;
+
+        Color::from(red.max(0f64), green.max(0f64), blue.max(0f64), 1f64) * self.intensity
+    }
+}
+
+impl Environment for PreethamSky {
+    fn get_radiance(&self, direction: Vector) -> Color {
+        self.get_sky_radiance(direction)
+    }
+}
+
+impl LightSource for PreethamSky {
+    fn get_light_direction_from(&self, _point: Vector) -> Vector {
+        -UP
+    }
+
+    fn get_light_distance_from(&self, _point: Vector) -> f64 {
+        f64::INFINITY
+    }
+
+    // the zenith stands in for the whole sky when it is treated as a single light
+    fn get_light_color(&self, _point: Vector) -> Color {
+        self.get_sky_radiance(UP) * PI
+    }
+
+    fn get_shadow_sample_count(&self) -> usize {
+        self.shadow_sample_count
+    }
+
+    fn emit_photon(&self, scene_center: Vector, scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+        environment::emit_photon(self, scene_center, scene_radius, random)
+    }
+
+    // the sky only covers the upper hemisphere, which is sampled uniformly
+    fn sample_from(&self, _point: Vector, random: &mut Random) -> LightSample {
+        let direction = sampling::uniform_hemisphere(UP, random);
+
+        LightSample { direction: -direction, distance: f64::INFINITY, color: self.get_sky_radiance(direction) * (2f64 * PI) }
+    }
+}
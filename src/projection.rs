@@ -0,0 +1,55 @@
+use crate::basis::Basis;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+// screen coordinates go from -1 to 1, left to right and bottom to top
+pub trait Projection: Send + Sync {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, aspect_ratio: f64, random: &mut Random) -> Option<Ray>;
+
+    // where a point straight ahead of the camera at the given distance lands on screen when seen from an eye moved
+    // sideways by eye_offset along right_of(basis), projections without a flat sensor keep such eyes converged at infinity
+    fn get_screen_x_from_eye(&self, _eye_offset: f64, _distance: f64) -> f64 {
+        0f64
+    }
+}
+
+pub fn right_of(basis: &Basis) -> Vector {
+    -basis.u
+}
+
+pub fn forward_of(basis: &Basis) -> Vector {
+    -basis.w
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::cylindrical_projection::CylindricalProjection;
+    use crate::equirectangular_projection::EquirectangularProjection;
+    use crate::fisheye_projection::FisheyeProjection;
+    use crate::orthographic_projection::OrthographicProjection;
+    use crate::perspective_projection::PerspectiveProjection;
+
+    #[test]
+    fn every_projection_looks_forward_at_the_image_center() {
+        let basis = Basis::from_wv(Vector::from(1f64, 2f64, 3f64), Vector::from(0f64, 1f64, 0f64)).unwrap();
+        let position = Vector::from(4f64, -1f64, 2f64);
+        let projections: Vec<Box<dyn Projection>> = vec![
+            Box::new(PerspectiveProjection::from(0.035f64, Vector::from(0.036f64, 0.024f64, 0f64)).unwrap()),
+            Box::new(OrthographicProjection::from(Vector::from(4f64, 3f64, 0f64)).unwrap()),
+            Box::new(FisheyeProjection::from(PI).unwrap()),
+            Box::new(EquirectangularProjection::from()),
+            Box::new(CylindricalProjection::from(PI, PI / 2f64).unwrap())
+        ];
+
+        for projection in projections {
+            let ray = projection.generate_ray(&basis, position, 0f64, 0f64, 1.5f64, &mut Random::from_seed(1)).unwrap();
+
+            assert!((ray.starting_point - position).length() < 1e-12);
+            assert!((ray.direction + basis.w).length() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+pub struct Random {
+    state: u64
+}
+
+impl Random {
+    pub fn from_seed(seed: u64) -> Random {
+        Random { state: Random::mix(seed.wrapping_add(0x9E37_79B9_7F4A_7C15)) }
+    }
+
+    pub fn from_pixel(x: usize, y: usize, pass: u64) -> Random {
+        let seed = Random::mix(x as u64) ^ Random::mix((y as u64).rotate_left(32)) ^ Random::mix(!pass);
+        Random::from_seed(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        Random::mix(self.state)
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn next_usize(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+
+        (self.next_f64() * bound as f64) as usize % bound
+    }
+
+    fn mix(value: u64) -> u64 {
+        let mut z = value;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
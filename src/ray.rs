@@ -3,13 +3,14 @@ use crate::vector::Vector;
 #[derive(Copy, Clone)]
 pub struct Ray {
     pub starting_point: Vector,
-    pub direction: Vector
+    pub direction: Vector,
+    pub time: f64
 }
 
 impl Ray {
     pub fn from(starting_point: Vector, direction: Vector) -> Option<Ray> {
         match direction.normalized() {
-            Some(direction) => Some(Ray { starting_point, direction }),
+            Some(direction) => Some(Ray { starting_point, direction, time: 0f64 }),
             None => None
         }
     }
@@ -19,6 +20,14 @@ impl Ray {
         Ray::from(starting_point, direction)
     }
 
+    pub fn at_time(self, time: f64) -> Ray {
+        Ray { time, ..self }
+    }
+
+    pub fn spawn(&self, starting_point: Vector, direction: Vector) -> Option<Ray> {
+        Ray::from(starting_point, direction).map(|ray| ray.at_time(self.time))
+    }
+
     pub fn point_at(&self, t: f64) -> Vector {
         self.starting_point + self.direction * t
     }
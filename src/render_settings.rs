@@ -0,0 +1,127 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::filter::{BoxFilter, Filter};
+use crate::integrator::Integrator;
+use crate::sample_pattern::SamplePattern;
+use crate::whitted_integrator::WhittedIntegrator;
+
+pub struct RenderSettings {
+    pub thread_count: usize,
+    pub tile_size: usize,
+    pub samples_per_pixel: usize,
+    pub sample_pattern: SamplePattern,
+    pub filter: Box<dyn Filter>,
+    pub adaptive_sampling: Option<AdaptiveSampling>,
+    pub integrator: Box<dyn Integrator>
+}
+
+impl RenderSettings {
+    pub fn from(thread_count: usize, tile_size: usize, samples_per_pixel: usize, sample_pattern: SamplePattern, filter: Box<dyn Filter>, integrator: Box<dyn Integrator>) -> Option<RenderSettings> {
+        if thread_count == 0 || tile_size == 0 || samples_per_pixel == 0 {
+            return None;
+        }
+
+        Some(RenderSettings { thread_count, tile_size, samples_per_pixel, sample_pattern, filter, adaptive_sampling: None, integrator })
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        let thread_count = thread::available_parallelism().map(|count| count.get()).unwrap_or(1);
+        let filter = Box::new(BoxFilter::from(0.5f64).unwrap());
+
+        RenderSettings { thread_count, tile_size: 32, samples_per_pixel: 1, sample_pattern: SamplePattern::Grid, filter, adaptive_sampling: None, integrator: Box::new(WhittedIntegrator::from(3)) }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AdaptiveSampling {
+    pub max_samples_per_pixel: usize,
+    pub error_threshold: f64
+}
+
+impl AdaptiveSampling {
+    pub fn from(max_samples_per_pixel: usize, error_threshold: f64) -> Option<AdaptiveSampling> {
+        if max_samples_per_pixel == 0 || error_threshold <= 0f64 {
+            return None;
+        }
+
+        Some(AdaptiveSampling { max_samples_per_pixel, error_threshold })
+    }
+
+    pub fn relative_error(luminance_sum: f64, luminance_squared_sum: f64, sample_count: usize) -> f64 {
+        if sample_count < 2 {
+            return f64::INFINITY;
+        }
+
+        let count = sample_count as f64;
+        let mean = luminance_sum / count;
+        let variance = ((luminance_squared_sum - luminance_sum * mean) / (count - 1f64)).max(0f64);
+
+        (variance / count).sqrt() / mean.max(1e-3f64)
+    }
+}
+
+pub struct ProgressiveSettings {
+    pub pass_count: usize,
+    pub output_path: String,
+    pub write_every_passes: Option<usize>,
+    pub write_every: Option<Duration>
+}
+
+impl ProgressiveSettings {
+    pub fn from(pass_count: usize, output_path: &str, write_every_passes: Option<usize>, write_every: Option<Duration>) -> Option<ProgressiveSettings> {
+        if pass_count == 0 || write_every_passes == Some(0) {
+            return None;
+        }
+
+        Some(ProgressiveSettings { pass_count, output_path: output_path.to_string(), write_every_passes, write_every })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative_error_of(luminances: &[f64]) -> f64 {
+        AdaptiveSampling::relative_error(luminances.iter().sum(), luminances.iter().map(|luminance| luminance * luminance).sum(), luminances.len())
+    }
+
+    #[test]
+    fn a_single_sample_never_stops_adaptive_sampling() {
+        assert_eq!(relative_error_of(&[]), f64::INFINITY);
+        assert_eq!(relative_error_of(&[0.5f64]), f64::INFINITY);
+    }
+
+    #[test]
+    fn relative_error_is_the_standard_error_over_the_mean() {
+        // a mean of 2 with a sample variance of 2 leaves a standard error of 1
+        assert!((relative_error_of(&[1f64, 3f64]) - 0.5f64).abs() < 1e-12);
+        assert_eq!(relative_error_of(&[0.25f64; 8]), 0f64);
+    }
+
+    #[test]
+    fn relative_error_shrinks_with_the_square_root_of_the_sample_count() {
+        let few = relative_error_of(&[1f64, 3f64, 1f64, 3f64]);
+        let many = relative_error_of(&[1f64, 3f64].repeat(8));
+
+        // the sample variance also shifts a little with its n - 1 denominator
+        let expected_ratio = 2f64 * ((4f64 / 3f64) / (16f64 / 15f64)).sqrt();
+        assert!((few / many - expected_ratio).abs() < 1e-9, "{}", few / many);
+    }
+
+    #[test]
+    fn dark_pixels_are_measured_against_a_minimum_brightness() {
+        // without the floor a black pixel with a single speck of light would never count as converged
+        let error = relative_error_of(&[0f64, 0f64, 0f64, 1e-6f64]);
+        assert!(error < 1e-3f64, "{}", error);
+    }
+
+    #[test]
+    fn adaptive_sampling_needs_samples_and_a_positive_threshold() {
+        assert!(AdaptiveSampling::from(0, 0.01f64).is_none());
+        assert!(AdaptiveSampling::from(16, 0f64).is_none());
+        assert_eq!(AdaptiveSampling::from(16, 0.01f64), Some(AdaptiveSampling { max_samples_per_pixel: 16, error_threshold: 0.01f64 }));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::random::Random;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SamplePattern {
+    Grid,
+    Jittered,
+    Stratified
+}
+
+impl SamplePattern {
+    pub fn pixel_offsets(&self, sample_count: usize, random: &mut Random) -> Vec<(f64, f64)> {
+        let sample_count = sample_count.max(1);
+
+        match self {
+            SamplePattern::Jittered => (0..sample_count).map(|_| (random.next_f64(), random.next_f64())).collect(),
+            SamplePattern::Grid => SamplePattern::cells(sample_count)
+                .map(|(x, y, width, height)| (x + width / 2f64, y + height / 2f64))
+                .collect(),
+            SamplePattern::Stratified => SamplePattern::cells(sample_count)
+                .map(|(x, y, width, height)| (x + width * random.next_f64(), y + height * random.next_f64()))
+                .collect()
+        }
+    }
+
+    pub fn refinement(&self) -> SamplePattern {
+        match self {
+            SamplePattern::Grid => SamplePattern::Stratified,
+            pattern => *pattern
+        }
+    }
+
+    fn cells(sample_count: usize) -> impl Iterator<Item = (f64, f64, f64, f64)> {
+        let rows = ((sample_count as f64).sqrt().round() as usize).max(1);
+        let row_height = 1f64 / rows as f64;
+
+        (0..rows).flat_map(move |row| {
+            let columns = sample_count / rows + usize::from(row < sample_count % rows);
+            let column_width = 1f64 / columns as f64;
+
+            (0..columns).map(move |column| (column as f64 * column_width, row as f64 * row_height, column_width, row_height))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_offsets(offsets: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        assert_eq!(offsets.len(), expected.len(), "{:?}", offsets);
+
+        for ((x, y), (expected_x, expected_y)) in offsets.iter().zip(expected) {
+            assert!((x - expected_x).abs() < 1e-12 && (y - expected_y).abs() < 1e-12, "{:?}", offsets);
+        }
+    }
+
+    #[test]
+    fn grid_samples_hit_the_sub_pixel_centers() {
+        let mut random = Random::from_seed(1);
+
+        assert_offsets(SamplePattern::Grid.pixel_offsets(1, &mut random), &[(0.5f64, 0.5f64)]);
+        assert_offsets(SamplePattern::Grid.pixel_offsets(4, &mut random), &[(0.25f64, 0.25f64), (0.75f64, 0.25f64), (0.25f64, 0.75f64), (0.75f64, 0.75f64)]);
+
+        // counts that are not square give the first rows an extra column
+        assert_offsets(SamplePattern::Grid.pixel_offsets(3, &mut random), &[(0.25f64, 0.25f64), (0.75f64, 0.25f64), (0.5f64, 0.75f64)]);
+    }
+
+    #[test]
+    fn stratified_samples_stay_inside_their_cells() {
+        let mut random = Random::from_seed(2);
+
+        for sample_count in [1, 2, 4, 5, 9, 12, 16] {
+            for _ in 0..100 {
+                let offsets = SamplePattern::Stratified.pixel_offsets(sample_count, &mut random);
+                assert_eq!(offsets.len(), sample_count);
+
+                for ((x, y), (cell_x, cell_y, width, height)) in offsets.into_iter().zip(SamplePattern::cells(sample_count)) {
+                    assert!(x >= cell_x && x < cell_x + width && y >= cell_y && y < cell_y + height, "{} {} outside {:?}", x, y, (cell_x, cell_y, width, height));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cells_cover_the_pixel_without_overlapping() {
+        for sample_count in [1, 2, 3, 5, 7, 12, 16] {
+            let area: f64 = SamplePattern::cells(sample_count).map(|(_, _, width, height)| width * height).sum();
+
+            assert_eq!(SamplePattern::cells(sample_count).count(), sample_count);
+            assert!((area - 1f64).abs() < 1e-12, "{} cells cover {}", sample_count, area);
+        }
+    }
+
+    #[test]
+    fn jittered_samples_stay_inside_the_pixel() {
+        let mut random = Random::from_seed(3);
+        let offsets = SamplePattern::Jittered.pixel_offsets(1000, &mut random);
+
+        assert_eq!(offsets.len(), 1000);
+        assert!(offsets.iter().all(|(x, y)| (0f64..1f64).contains(x) && (0f64..1f64).contains(y)));
+    }
+
+    #[test]
+    fn refinement_only_replaces_the_fixed_grid() {
+        assert_eq!(SamplePattern::Grid.refinement(), SamplePattern::Stratified);
+        assert_eq!(SamplePattern::Jittered.refinement(), SamplePattern::Jittered);
+        assert_eq!(SamplePattern::Stratified.refinement(), SamplePattern::Stratified);
+    }
+}
@@ -0,0 +1,51 @@
+use std::f64::consts::PI;
+
+use crate::basis::Basis;
+use crate::random::Random;
+use crate::vector::Vector;
+
+pub fn cosine_weighted_hemisphere(normal: Vector, random: &mut Random) -> Vector {
+    let (disk_x, disk_y) = concentric_disk(random);
+    let height = (1f64 - disk_x * disk_x - disk_y * disk_y).max(0f64).sqrt();
+
+    match Basis::from_w(normal) {
+        Some(basis) => basis.to_world(Vector::from(disk_x, disk_y, height)).normalized_or_zero(),
+        None => normal
+    }
+}
+
+pub fn concentric_disk(random: &mut Random) -> (f64, f64) {
+    let x = 2f64 * random.next_f64() - 1f64;
+    let y = 2f64 * random.next_f64() - 1f64;
+
+    if x == 0f64 && y == 0f64 {
+        return (0f64, 0f64);
+    }
+
+    let (radius, angle) = if x.abs() > y.abs() {
+        (x, PI / 4f64 * (y / x))
+    } else {
+        (y, PI / 2f64 - PI / 4f64 * (x / y))
+    };
+
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+pub fn uniform_hemisphere(normal: Vector, random: &mut Random) -> Vector {
+    let height = random.next_f64();
+    let radius = (1f64 - height * height).max(0f64).sqrt();
+    let angle = 2f64 * PI * random.next_f64();
+
+    match Basis::from_w(normal) {
+        Some(basis) => basis.to_world(Vector::from(radius * angle.cos(), radius * angle.sin(), height)).normalized_or_zero(),
+        None => normal
+    }
+}
+
+pub fn uniform_sphere(random: &mut Random) -> Vector {
+    let height = 1f64 - 2f64 * random.next_f64();
+    let radius = (1f64 - height * height).max(0f64).sqrt();
+    let angle = 2f64 * PI * random.next_f64();
+
+    Vector::from(radius * angle.cos(), radius * angle.sin(), height)
+}
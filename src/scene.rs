@@ -0,0 +1,220 @@
+use crate::color::Color;
+use crate::environment::Environment;
+use crate::integrator::SURFACE_OFFSET;
+use crate::light_sampler::LightSampler;
+use crate::light_source::LightSource;
+use crate::medium::MediumRegion;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::surface::{RayIntersectionResult, Surface};
+use crate::vector::Vector;
+
+const MAX_SHADOW_RAY_HITS: usize = 16;
+
+pub struct Scene<'a> {
+    pub surface: &'a dyn Surface,
+    pub light_sources: Vec<&'a dyn LightSource>,
+    // only seen by rays that miss everything, an environment that should light diffuse surfaces also goes in light_sources,
+    // the same way emissive surfaces do through their area lights
+    pub environment: Option<&'a dyn Environment>,
+    // every light is evaluated at every hit when there is no sampler
+    pub light_sampler: Option<LightSampler>,
+    // fog, haze and smoke the rays travel through, only light scattered once on its way from a light source is gathered
+    pub media: Vec<MediumRegion<'a>>
+}
+
+impl<'a> Scene<'a> {
+    pub fn from(surface: &'a dyn Surface, light_sources: Vec<&'a dyn LightSource>) -> Scene<'a> {
+        Scene { surface, light_sources, environment: None, light_sampler: None, media: Vec::new() }
+    }
+
+    pub fn get_background_color(&self, direction: Vector) -> Color {
+        match self.environment {
+            Some(environment) => environment.get_radiance(direction),
+            None => Color::BLACK
+        }
+    }
+
+    // each picked light comes with the weight that keeps the sum over the picks unbiased
+    pub fn select_light_sources(&self, point: Vector, random: &mut Random) -> Vec<(&'a dyn LightSource, f64)> {
+        match &self.light_sampler {
+            Some(light_sampler) => (0..light_sampler.light_sample_count)
+                .filter_map(|_| {
+                    let (index, probability) = light_sampler.sample(point, random);
+                    let light_source = *self.light_sources.get(index)?;
+
+                    if probability > 0f64 { Some((light_source, 1f64 / (probability * light_sampler.light_sample_count as f64))) } else { None }
+                })
+                .collect(),
+            None => self.light_sources.iter().map(|light_source| (*light_source, 1f64)).collect()
+        }
+    }
+
+    // emissive surfaces without an area light can only be found by rays that happen to hit them
+    pub fn is_sampled_emitter(&self, point: Vector) -> bool {
+        self.light_sources.iter().any(|light_source| light_source.covers_point(point))
+    }
+
+    // anything past the light does not cast a shadow on it
+    pub fn is_unoccluded(&self, shadow_ray: Ray, light_distance: f64) -> bool {
+        match self.surface.intersect_with_ray(shadow_ray) {
+            RayIntersectionResult::Intersected { t, .. } => t + SURFACE_OFFSET >= light_distance,
+            RayIntersectionResult::NoIntersection => true
+        }
+    }
+
+    // shadow rays go straight through transparent surfaces, picking up their tint and absorption on the way
+    pub fn get_shadow_transmittance(&self, shadow_ray: Ray, light_distance: f64) -> Color {
+        let (mut ray, mut remaining_distance, mut transmittance) = (shadow_ray, light_distance, Color::WHITE);
+
+        for hit in 0..MAX_SHADOW_RAY_HITS {
+            let (material, normal, t, front_face) = match self.surface.intersect_with_ray(ray) {
+                RayIntersectionResult::Intersected { surface_material, surface_normal, t, front_face } if t + SURFACE_OFFSET < remaining_distance => (surface_material, surface_normal, t, front_face),
+                _ => return transmittance
+            };
+
+            if material.transmission_coefficient <= 0f64 {
+                return Color::ZERO;
+            }
+
+            let relative_refractive_index = if front_face { 1f64 / material.refractive_index } else { material.refractive_index };
+            let reflectance = material.fresnel_model.reflectance(ray.direction.dot(normal).abs(), relative_refractive_index);
+
+            transmittance = transmittance * (1f64 - reflectance);
+
+            // every interface reflects some of the light away, but the tint is picked up once per object, when the ray goes in
+            // or at its first hit when it starts inside
+            if front_face || hit == 0 {
+                transmittance = transmittance * material.color * material.transmission_coefficient;
+            }
+
+            // a back face hit means the shadow ray travelled through the material to get here
+            if !front_face {
+                transmittance = transmittance * material.transmittance_over(t);
+            }
+
+            if transmittance.max_component() <= 0f64 {
+                return Color::ZERO;
+            }
+
+            ray = match ray.spawn(ray.point_at(t + SURFACE_OFFSET), ray.direction) {
+                Some(ray) => ray,
+                None => return Color::ZERO
+            };
+            remaining_distance -= t + SURFACE_OFFSET;
+        }
+
+        Color::ZERO
+    }
+
+    pub fn get_medium_transmittance(&self, ray: Ray, distance: f64, random: &mut Random) -> Color {
+        self.get_medium_transmittance_skipping(ray, distance, None, random)
+    }
+
+    // the skipped interval of one region is left out, its own transmittance is already part of a scattering sample
+    fn get_medium_transmittance_skipping(&self, ray: Ray, distance: f64, skipped: Option<(usize, f64)>, random: &mut Random) -> Color {
+        let mut transmittance = Color::WHITE;
+
+        for (region_index, region) in self.media.iter().enumerate() {
+            for (start, end) in region.get_intervals(ray, distance) {
+                if skipped == Some((region_index, start)) {
+                    continue;
+                }
+
+                transmittance = transmittance * region.medium.get_transmittance(ray, start, end, random);
+            }
+        }
+
+        transmittance
+    }
+
+    // light given off or scattered towards the ray origin by the media along the first distance units of the ray
+    pub fn get_medium_radiance(&self, ray: Ray, distance: f64, random: &mut Random) -> Color {
+        let mut scattered = Color::BLACK;
+
+        for (region_index, region) in self.media.iter().enumerate() {
+            for (start, end) in region.get_intervals(ray, distance) {
+                if let Some((t, weight)) = region.medium.sample_emission(ray, start, end, random) {
+                    scattered = scattered + self.get_medium_transmittance_skipping(ray, t, Some((region_index, start)), random) * weight;
+                }
+
+                let (t, weight) = match region.medium.sample_scattering(ray, start, end, random) {
+                    Some(sample) => sample,
+                    None => continue
+                };
+
+                let point = ray.point_at(t);
+                let transmittance_before = self.get_medium_transmittance_skipping(ray, t, Some((region_index, start)), random) * weight;
+
+                for (light_source, light_weight) in self.select_light_sources(point, random) {
+                    let light_sample = light_source.sample_from(point, random);
+
+                    let shadow_ray = match ray.spawn(point, -light_sample.direction) {
+                        Some(shadow_ray) => shadow_ray,
+                        None => continue
+                    };
+
+                    if !self.is_unoccluded(shadow_ray, light_sample.distance) {
+                        continue;
+                    }
+
+                    // the phase function is evaluated between the light's travel direction and the direction back along the ray
+                    let phase = region.medium.get_phase(light_sample.direction.dot(-ray.direction));
+                    let light_transmittance = self.get_medium_transmittance(shadow_ray, light_sample.distance, random);
+
+                    scattered = scattered + transmittance_before * light_sample.color * light_transmittance * (phase * light_weight);
+                }
+            }
+        }
+
+        Color::from(scattered.red, scattered.green, scattered.blue, 0f64)
+    }
+
+    // what is left of the color arriving from the given distance, plus the light the media scatter into the ray on the way
+    pub fn apply_media(&self, ray: Ray, distance: f64, color: Color, random: &mut Random) -> Color {
+        if self.media.is_empty() {
+            return color;
+        }
+
+        self.get_medium_transmittance(ray, distance, random) * color + self.get_medium_radiance(ray, distance, random)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+
+    fn transmittance_through(material: Material) -> Color {
+        let glass = Sphere::from(Vector::from(0f64, 0f64, 10f64), 2f64, material).unwrap();
+        let scene = Scene::from(&glass, Vec::new());
+        let shadow_ray = Ray::from(Vector::ZERO, Vector::from(0f64, 0f64, 1f64)).unwrap();
+
+        scene.get_shadow_transmittance(shadow_ray, 20f64)
+    }
+
+    #[test]
+    fn shadow_ray_loses_light_at_both_interfaces_of_a_glass_sphere() {
+        let transmittance = transmittance_through(Material::dielectric(Color::WHITE, 1.5f64, Color::ZERO).unwrap());
+
+        // head on each interface reflects 4% away
+        assert!((transmittance.red - 0.96f64 * 0.96f64).abs() < 1e-6, "{}", transmittance.red);
+    }
+
+    #[test]
+    fn shadow_ray_is_tinted_once_and_absorbed_inside_a_glass_sphere() {
+        let transmittance = transmittance_through(Material::dielectric(Color::from(0.5f64, 1f64, 1f64, 1f64), 1.5f64, Color::from(0f64, 0.1f64, 0f64, 0f64)).unwrap());
+
+        assert!((transmittance.red - 0.5f64 * 0.96f64 * 0.96f64).abs() < 1e-6, "{}", transmittance.red);
+        assert!((transmittance.green - 0.96f64 * 0.96f64 * (-0.4f64).exp()).abs() < 1e-4, "{}", transmittance.green);
+        assert!((transmittance.blue - 0.96f64 * 0.96f64).abs() < 1e-6, "{}", transmittance.blue);
+    }
+
+    #[test]
+    fn shadow_ray_is_blocked_by_an_opaque_sphere() {
+        let transmittance = transmittance_through(Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64));
+
+        assert_eq!(transmittance.max_component(), 0f64);
+    }
+}
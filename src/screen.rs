@@ -1,3 +1,7 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Write};
 
 use crate::color::Color;
 
@@ -12,6 +16,7 @@ impl Screen {
         Screen { pixels: vec![default_color; width * height], width, height }
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn paint_at(&mut self, x: usize, y: usize, color: Color) -> Result<(), ()> {
         if !self.is_valid_point(x, y) {
             return Err(());
@@ -21,6 +26,15 @@ impl Screen {
         Ok(())
     }
 
+    #[allow(clippy::result_unit_err)]
+    pub fn paint_screen_at(&mut self, x: usize, y: usize, screen: &Screen) -> Result<(), ()> {
+        if screen.width == 0 || screen.height == 0 {
+            return Ok(());
+        }
+
+        self.paint_tile(Tile { x, y, width: screen.width, height: screen.height }, &screen.pixels)
+    }
+
     pub fn get_color_at(&self, x: usize, y: usize) -> Option<Color> {
         if !self.is_valid_point(x, y) {
             return None;
@@ -29,10 +43,33 @@ impl Screen {
         Some(self.pixels[self.width * y + x])
     }
 
+    #[allow(clippy::result_unit_err)]
+    pub fn paint_tile(&mut self, tile: Tile, colors: &[Color]) -> Result<(), ()> {
+        // empty tiles have no last pixel to check, and tiles reaching past usize cannot fit either
+        let last_x = tile.x.checked_add(tile.width).and_then(|end| end.checked_sub(1));
+        let last_y = tile.y.checked_add(tile.height).and_then(|end| end.checked_sub(1));
+
+        match (last_x, last_y) {
+            (Some(last_x), Some(last_y)) if tile.width > 0 && tile.height > 0 && self.is_valid_point(last_x, last_y) && colors.len() == tile.width * tile.height => {},
+            _ => return Err(())
+        }
+
+        for (row, row_colors) in colors.chunks(tile.width).enumerate() {
+            let start = self.width * (tile.y + row) + tile.x;
+            self.pixels[start..start + tile.width].copy_from_slice(row_colors);
+        }
+
+        Ok(())
+    }
+
     pub fn pixel_positions(&self) -> ScreenPixelPositions {
         ScreenPixelPositions { x: 0, y: 0, width: self.width, height: self.height }
     }
 
+    pub fn tiles(&self, tile_size: usize) -> ScreenTiles {
+        ScreenTiles::from(self.width, self.height, tile_size)
+    }
+
     pub fn is_valid_point(&self, x: usize, y: usize) -> bool {
         x < self.width && y < self.height
     }
@@ -47,6 +84,182 @@ impl Screen {
         
         format!("P3\n{} {}\n255\n{}", self.width, self.height, colors)
     }
+
+    pub fn read_pbm(path: &str) -> io::Result<Screen> {
+        Screen::from_pbm_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_pbm_bytes(bytes: &[u8]) -> io::Result<Screen> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        // header tokens are whitespace separated and may be interleaved with comments
+        let mut header = Vec::new();
+        let mut position = 0;
+
+        while header.len() < 4 && position < bytes.len() {
+            match bytes[position] {
+                b'#' => while position < bytes.len() && bytes[position] != b'\n' { position += 1 },
+                byte if byte.is_ascii_whitespace() => position += 1,
+                _ => {
+                    let start = position;
+                    while position < bytes.len() && !bytes[position].is_ascii_whitespace() { position += 1 }
+                    header.push(String::from_utf8_lossy(&bytes[start..position]).to_string());
+                }
+            }
+        }
+
+        if header.len() < 4 {
+            return Err(invalid("incomplete header"));
+        }
+
+        let width: usize = header[1].parse().map_err(|_| invalid("invalid width"))?;
+        let height: usize = header[2].parse().map_err(|_| invalid("invalid height"))?;
+        let max_value: f64 = header[3].parse().map_err(|_| invalid("invalid max value"))?;
+
+        if max_value <= 0f64 {
+            return Err(invalid("invalid max value"));
+        }
+
+        let values: Vec<f64> = match header[0].as_str() {
+            "P3" => String::from_utf8_lossy(&bytes[position..]).split_whitespace()
+                .map(|value| value.parse::<f64>().map_err(|_| invalid("invalid color value")))
+                .collect::<io::Result<Vec<f64>>>()?,
+            "P6" if max_value < 256f64 => bytes.get(position + 1..).unwrap_or(&[]).iter().map(|byte| *byte as f64).collect(),
+            "P6" => bytes.get(position + 1..).unwrap_or(&[]).chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as f64).collect(),
+            _ => return Err(invalid("unsupported format"))
+        };
+
+        if values.len() < width * height * 3 {
+            return Err(invalid("not enough color values"));
+        }
+
+        let pixels = values.chunks_exact(3).take(width * height)
+            .map(|rgb| Color::from(rgb[0] / max_value, rgb[1] / max_value, rgb[2] / max_value, 1f64))
+            .collect();
+
+        Ok(Screen { pixels, width, height })
+    }
+
+    pub fn read_hdr(path: &str) -> io::Result<Screen> {
+        Screen::from_hdr_bytes(&fs::read(path)?)
+    }
+
+    // radiance rgbe images, either flat or with the per channel run length encoding
+    pub fn from_hdr_bytes(bytes: &[u8]) -> io::Result<Screen> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        let mut position = 0;
+        let mut next_line = || {
+            if position >= bytes.len() {
+                return Err(invalid("incomplete header"));
+            }
+
+            let start = position;
+            while position < bytes.len() && bytes[position] != b'\n' { position += 1 }
+            let line = String::from_utf8_lossy(&bytes[start..position]).to_string();
+            position += 1;
+            Ok(line)
+        };
+
+        if !next_line()?.starts_with("#?") {
+            return Err(invalid("missing radiance signature"));
+        }
+
+        loop {
+            let line = next_line()?;
+
+            if line.is_empty() {
+                break;
+            }
+
+            if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+                return Err(invalid("unsupported pixel format"));
+            }
+        }
+
+        let resolution: Vec<String> = next_line()?.split_whitespace().map(str::to_string).collect();
+        if resolution.len() != 4 || resolution[0] != "-Y" || resolution[2] != "+X" {
+            return Err(invalid("unsupported resolution line"));
+        }
+
+        let height: usize = resolution[1].parse().map_err(|_| invalid("invalid height"))?;
+        let width: usize = resolution[3].parse().map_err(|_| invalid("invalid width"))?;
+
+        let byte_at = |index: &mut usize| {
+            let byte = bytes.get(*index).copied().ok_or_else(|| invalid("not enough pixel data"));
+            *index += 1;
+            byte
+        };
+
+        // only run length encoded scanlines can be shorter than four bytes per pixel, and they are narrower than 0x8000
+        let remaining = bytes.len().saturating_sub(position);
+        if width == 0 || height == 0 || (width >= 0x8000 && width.saturating_mul(4) > remaining) {
+            return Err(invalid("not enough pixel data"));
+        }
+
+        // grows with every scanline read, so a header promising more pixels than the file holds fails before allocating them
+        let mut rgbe = Vec::new();
+        let mut scanline = vec![0u8; width * 4];
+
+        for _ in 0..height {
+            let is_run_length_encoded = (8..0x8000).contains(&width)
+                && bytes.get(position..position + 4).is_some_and(|start| start[0] == 2 && start[1] == 2 && ((start[2] as usize) << 8 | start[3] as usize) == width);
+
+            if !is_run_length_encoded {
+                for value in scanline.iter_mut() {
+                    *value = byte_at(&mut position)?;
+                }
+
+                rgbe.extend_from_slice(&scanline);
+                continue;
+            }
+
+            position += 4;
+
+            for channel in 0..4 {
+                let mut x = 0;
+
+                while x < width {
+                    let count = byte_at(&mut position)? as usize;
+
+                    // counts above 128 repeat the next byte, smaller counts are followed by that many literal bytes
+                    let (length, repeated_value) = if count > 128 { (count - 128, Some(byte_at(&mut position)?)) } else { (count, None) };
+
+                    if length == 0 || x + length > width {
+                        return Err(invalid("invalid run length"));
+                    }
+
+                    for _ in 0..length {
+                        scanline[x * 4 + channel] = match repeated_value {
+                            Some(value) => value,
+                            None => byte_at(&mut position)?
+                        };
+                        x += 1;
+                    }
+                }
+            }
+
+            rgbe.extend_from_slice(&scanline);
+        }
+
+        let pixels = rgbe.chunks_exact(4)
+            .map(|rgbe| {
+                if rgbe[3] == 0 {
+                    return Color::BLACK;
+                }
+
+                let scale = 2f64.powi(rgbe[3] as i32 - 136);
+                Color::from(rgbe[0] as f64 * scale, rgbe[1] as f64 * scale, rgbe[2] as f64 * scale, 1f64)
+            })
+            .collect();
+
+        Ok(Screen { pixels, width, height })
+    }
+
+    pub fn write_pbm(&self, path: &str) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(self.to_pbm().as_ref())
+    }
 }
 
 pub struct ScreenPixelPositions {
@@ -73,4 +286,138 @@ impl Iterator for ScreenPixelPositions {
         self.x += 1;
         Some(current)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Tile {
+    pub fn pixel_positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let tile = *self;
+        (tile.y..tile.y + tile.height).flat_map(move |y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+    }
+}
+
+pub struct ScreenTiles {
+    x: usize,
+    y: usize,
+    tile_size: usize,
+    width: usize,
+    height: usize
+}
+
+impl ScreenTiles {
+    // tiles only depend on the resolution, so they can be listed without a screen to back them
+    pub fn from(width: usize, height: usize, tile_size: usize) -> ScreenTiles {
+        ScreenTiles { x: 0, y: 0, tile_size: tile_size.max(1), width, height }
+    }
+}
+
+impl Iterator for ScreenTiles {
+    type Item = Tile;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.x >= self.width {
+            self.x = 0;
+            self.y += self.tile_size;
+        }
+
+        if self.y >= self.height || self.width == 0 {
+            return None;
+        }
+
+        let current = Tile {
+            x: self.x,
+            y: self.y,
+            width: self.tile_size.min(self.width - self.x),
+            height: self.tile_size.min(self.height - self.y)
+        };
+
+        self.x += self.tile_size;
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &[u8] = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n";
+
+    fn hdr_bytes(resolution: &str, pixel_data: &[u8]) -> Vec<u8> {
+        [HEADER, resolution.as_bytes(), b"\n", pixel_data].concat()
+    }
+
+    fn assert_color(color: Option<Color>, red: f64, green: f64, blue: f64) {
+        let color = color.unwrap();
+        assert_eq!((color.red, color.green, color.blue), (red, green, blue));
+    }
+
+    #[test]
+    fn tiles_are_painted_only_when_they_fit() {
+        let mut screen = Screen::from(3, 2, Color::BLACK);
+
+        assert!(screen.paint_tile(Tile { x: 1, y: 0, width: 2, height: 2 }, &[Color::WHITE; 4]).is_ok());
+        assert_color(screen.get_color_at(2, 1), 1f64, 1f64, 1f64);
+        assert_color(screen.get_color_at(0, 1), 0f64, 0f64, 0f64);
+
+        assert!(screen.paint_tile(Tile { x: 0, y: 0, width: 0, height: 2 }, &[]).is_err());
+        assert!(screen.paint_tile(Tile { x: 0, y: 0, width: 2, height: 0 }, &[]).is_err());
+        assert!(screen.paint_tile(Tile { x: 2, y: 0, width: 2, height: 1 }, &[Color::WHITE; 2]).is_err());
+        assert!(screen.paint_tile(Tile { x: usize::MAX, y: 0, width: 2, height: 1 }, &[Color::WHITE; 2]).is_err());
+        assert!(screen.paint_tile(Tile { x: 0, y: 0, width: 2, height: 1 }, &[Color::WHITE; 3]).is_err());
+    }
+
+    #[test]
+    fn flat_hdr_pixels_are_decoded() {
+        let screen = Screen::from_hdr_bytes(&hdr_bytes("-Y 1 +X 2", &[128, 64, 32, 129, 0, 0, 0, 0])).unwrap();
+
+        assert_eq!(screen.resolution(), (2, 1));
+        assert_color(screen.get_color_at(0, 0), 1f64, 0.5f64, 0.25f64);
+        assert_color(screen.get_color_at(1, 0), 0f64, 0f64, 0f64);
+    }
+
+    #[test]
+    fn run_length_encoded_hdr_pixels_are_decoded() {
+        let pixel_data = [
+            &[2u8, 2, 0, 8][..],
+            &[136, 128],
+            &[136, 64],
+            &[8, 0, 16, 32, 48, 64, 80, 96, 112],
+            &[136, 129]
+        ].concat();
+        let screen = Screen::from_hdr_bytes(&hdr_bytes("-Y 1 +X 8", &pixel_data)).unwrap();
+
+        assert_eq!(screen.resolution(), (8, 1));
+        for x in 0..8 {
+            assert_color(screen.get_color_at(x, 0), 1f64, 0.5f64, x as f64 * 0.125f64);
+        }
+    }
+
+    #[test]
+    fn truncated_hdr_headers_are_rejected() {
+        for bytes in [&b""[..], b"#?RADIANCE\n", b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe", b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n"] {
+            assert_eq!(Screen::from_hdr_bytes(bytes).err().map(|error| error.kind()), Some(ErrorKind::InvalidData));
+        }
+    }
+
+    #[test]
+    fn truncated_hdr_pixel_data_is_rejected() {
+        let truncated = [
+            hdr_bytes("-Y 1 +X 2", &[128, 64, 32, 129, 0, 0]),
+            hdr_bytes("-Y 1 +X 8", &[2, 2, 0, 8, 136, 128, 136]),
+            hdr_bytes("-Y 1 +X 8", &[2, 2, 0, 8, 136, 128, 136, 64, 8, 0, 16]),
+            hdr_bytes("-Y 100000 +X 100000", &[128, 64, 32, 129]),
+            hdr_bytes("-Y 0 +X 0", &[])
+        ];
+
+        for bytes in truncated {
+            assert_eq!(Screen::from_hdr_bytes(&bytes).err().map(|error| error.kind()), Some(ErrorKind::InvalidData));
+        }
+    }
+}
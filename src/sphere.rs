@@ -1,4 +1,5 @@
 
+use crate::area_light::{AreaLight, AreaLightShape};
 use crate::material::Material;
 use crate::surface::{RayIntersectionResult, Surface};
 use crate::ray::Ray;
@@ -7,22 +8,41 @@ use crate::vector::Vector;
 pub struct Sphere {
     material: Material,
     center: Vector,
+    velocity: Vector,
     radius: f64
 }
 
 impl Sphere {
     pub fn from(center: Vector, radius: f64, material: Material) -> Option<Sphere> {
+        Sphere::moving(center, Vector::ZERO, radius, material)
+    }
+
+    pub fn moving(center: Vector, velocity: Vector, radius: f64, material: Material) -> Option<Sphere> {
         if radius <= 0f64 {
             None
         } else {
-            Some(Sphere { center, radius, material })
+            Some(Sphere { center, velocity, radius, material })
+        }
+    }
+
+    pub fn center_at(&self, time: f64) -> Vector {
+        self.center + self.velocity * time
+    }
+
+    // lets integrators sample an emissive sphere directly, the light stays where the sphere is at time zero
+    pub fn to_area_light(&self, shadow_sample_count: usize) -> Option<AreaLight> {
+        if !self.material.is_emissive() {
+            return None;
         }
+
+        AreaLight::from(AreaLightShape::sphere(self.center, self.radius)?, self.material.emission_strength, self.material.emission, shadow_sample_count)
     }
 }
 
 impl Surface for Sphere {
     fn intersect_with_ray(&self, ray: Ray) -> RayIntersectionResult {
-        let distance: Vector = self.center - ray.starting_point;
+        let center = self.center_at(ray.time);
+        let distance: Vector = center - ray.starting_point;
         let determinant: f64 = ray.direction.length_squared() * self.radius.powi(2) - distance.cross(ray.direction).length_squared();
 
         if determinant.is_sign_negative() {
@@ -31,19 +51,63 @@ impl Surface for Sphere {
 
         let t1: f64 = (distance.dot(ray.direction) + determinant.sqrt()) / ray.direction.length_squared();
         let t2: f64 = (distance.dot(ray.direction) - determinant.sqrt()) / ray.direction.length_squared();
-        let t: f64 = if t1 < f64::EPSILON || t1 > t2 { t2 } else { t1 };
+        // t2 is the nearer root, rays starting inside the sphere only hit the farther one
+        let t: f64 = if t2 > f64::EPSILON { t2 } else { t1 };
 
         if t < f64::EPSILON {
             return RayIntersectionResult::NoIntersection
         }
 
-        let normal: Vector = (ray.point_at(t) - self.center).normalized_or_zero();
+        let normal: Vector = (ray.point_at(t) - center).normalized_or_zero();
         //println!("{}", self.color * (normal.dot(-distance.normalized_or_zero())));
 
         RayIntersectionResult::Intersected {
             surface_material: self.material,
             surface_normal: normal,
-            t
+            t,
+            front_face: normal.dot(ray.direction) < 0f64
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn test_sphere() -> Sphere {
+        Sphere::from(Vector::from(0f64, 0f64, 10f64), 2f64, Material::from(Color::WHITE, 1f64, 0f64, 1f64, 0f64)).unwrap()
+    }
+
+    #[test]
+    fn ray_from_outside_hits_the_front_face() {
+        let ray = Ray::from(Vector::ZERO, Vector::from(0f64, 0f64, 1f64)).unwrap();
+
+        match test_sphere().intersect_with_ray(ray) {
+            RayIntersectionResult::Intersected { t, front_face, .. } => {
+                assert!((t - 8f64).abs() < 1e-9);
+                assert!(front_face);
+            },
+            RayIntersectionResult::NoIntersection => panic!("expected a hit")
+        }
+    }
+
+    #[test]
+    fn ray_from_inside_hits_the_back_face() {
+        let ray = Ray::from(Vector::from(0f64, 0f64, 10f64), Vector::from(0f64, 0f64, 1f64)).unwrap();
+
+        match test_sphere().intersect_with_ray(ray) {
+            RayIntersectionResult::Intersected { t, front_face, .. } => {
+                assert!((t - 2f64).abs() < 1e-9);
+                assert!(!front_face);
+            },
+            RayIntersectionResult::NoIntersection => panic!("expected a hit")
+        }
+    }
+
+    #[test]
+    fn sphere_behind_the_ray_is_missed() {
+        let ray = Ray::from(Vector::ZERO, Vector::from(0f64, 0f64, -1f64)).unwrap();
+
+        assert!(matches!(test_sphere().intersect_with_ray(ray), RayIntersectionResult::NoIntersection));
+    }
+}
@@ -0,0 +1,189 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::basis::Basis;
+use crate::color::Color;
+use crate::light_source::LightSource;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::screen::Screen;
+use crate::vector::Vector;
+
+pub struct SpotLight {
+    position: Vector,
+    basis: Basis,
+    intensity: f64,
+    color: Color,
+    cos_inner_angle: f64,
+    cos_outer_angle: f64,
+    tan_outer_angle: f64,
+    pub gobo: Option<Screen>
+}
+
+impl SpotLight {
+    pub fn from(position: Vector, direction: Vector, intensity: f64, color: Color, inner_angle: f64, outer_angle: f64) -> Option<SpotLight> {
+        if intensity < 0f64 || inner_angle < 0f64 || inner_angle > outer_angle || outer_angle >= FRAC_PI_2 {
+            return None;
+        }
+
+        let basis = Basis::from_w(direction)?;
+
+        Some(SpotLight {
+            position,
+            basis,
+            intensity,
+            color,
+            cos_inner_angle: inner_angle.cos(),
+            cos_outer_angle: outer_angle.cos(),
+            tan_outer_angle: outer_angle.tan(),
+            gobo: None
+        })
+    }
+
+    fn get_cone_falloff(&self, cos_angle: f64) -> f64 {
+        if cos_angle >= self.cos_inner_angle {
+            return 1f64;
+        }
+
+        if cos_angle <= self.cos_outer_angle {
+            return 0f64;
+        }
+
+        let x = (cos_angle - self.cos_outer_angle) / (self.cos_inner_angle - self.cos_outer_angle);
+        x * x * (3f64 - 2f64 * x)
+    }
+
+    // the gobo covers the square that circumscribes the outer cone, one light unit away from the spot
+    fn get_gobo_color(&self, direction: Vector, cos_angle: f64) -> Color {
+        let gobo = match &self.gobo {
+            Some(gobo) => gobo,
+            None => return Color::WHITE
+        };
+
+        let (width, height) = gobo.resolution();
+        let x = direction.dot(self.basis.u) / (cos_angle * self.tan_outer_angle);
+        let y = direction.dot(self.basis.v) / (cos_angle * self.tan_outer_angle);
+
+        let pixel_x = ((x + 1f64) * 0.5f64 * width as f64).clamp(0f64, width as f64 - 1f64) as usize;
+        let pixel_y = ((1f64 - y) * 0.5f64 * height as f64).clamp(0f64, height as f64 - 1f64) as usize;
+
+        gobo.get_color_at(pixel_x, pixel_y).unwrap_or(Color::BLACK)
+    }
+}
+
+impl LightSource for SpotLight {
+    fn get_light_direction_from(&self, point: Vector) -> Vector {
+        (point - self.position).normalized_or_zero()
+    }
+
+    fn get_light_distance_from(&self, point: Vector) -> f64 {
+        (point - self.position).length()
+    }
+
+    fn get_light_color(&self, point: Vector) -> Color {
+        let squared_distance = (point - self.position).length_squared();
+
+        if squared_distance <= 0f64 {
+            return Color::ZERO;
+        }
+
+        let direction = (point - self.position).normalized_or_zero();
+        let cos_angle = direction.dot(self.basis.w);
+        let falloff = self.get_cone_falloff(cos_angle);
+
+        if falloff <= 0f64 {
+            return Color::ZERO;
+        }
+
+        self.color * self.get_gobo_color(direction, cos_angle) * (self.intensity * falloff / squared_distance)
+    }
+
+    // the falloff band counts as half lit
+    fn get_power(&self) -> f64 {
+        2f64 * PI * (1f64 - 0.5f64 * (self.cos_inner_angle + self.cos_outer_angle)) * self.intensity * self.color.luminance()
+    }
+
+    fn get_bounds(&self) -> Option<(Vector, Vector)> {
+        Some((self.position, self.position))
+    }
+
+    // directions are picked uniformly inside the outer cone, the falloff and the gobo then scale the power
+    fn emit_photon(&self, _scene_center: Vector, _scene_radius: f64, random: &mut Random) -> Option<(Ray, Color)> {
+        let cos_angle = 1f64 - random.next_f64() * (1f64 - self.cos_outer_angle);
+        let sin_angle = (1f64 - cos_angle * cos_angle).max(0f64).sqrt();
+        let rotation = 2f64 * PI * random.next_f64();
+        let direction = self.basis.to_world(Vector::from(sin_angle * rotation.cos(), sin_angle * rotation.sin(), cos_angle));
+
+        let cone_solid_angle = 2f64 * PI * (1f64 - self.cos_outer_angle);
+        let power = self.color * self.get_gobo_color(direction, cos_angle) * (self.intensity * self.get_cone_falloff(cos_angle) * cone_solid_angle);
+
+        Ray::from(self.position, direction).map(|ray| (ray, power))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // points one unit away from a spot at the origin shining down +z, at the given angle off its axis towards +u
+    fn color_at_angle(light: &SpotLight, angle: f64) -> Color {
+        light.get_light_color(light.basis.u * angle.sin() + light.basis.w * angle.cos())
+    }
+
+    fn spot_light() -> SpotLight {
+        SpotLight::from(Vector::ZERO, Vector::from(0f64, 0f64, 1f64), 2f64, Color::WHITE, 0.2f64, 0.4f64).unwrap()
+    }
+
+    #[test]
+    fn spot_light_is_full_inside_the_inner_cone_and_dark_outside_the_outer_one() {
+        let light = spot_light();
+
+        for angle in [0f64, 0.1f64, 0.199f64] {
+            assert!((color_at_angle(&light, angle).red - 2f64).abs() < 1e-12);
+        }
+        for angle in [0.401f64, 0.6f64, PI] {
+            assert_eq!(color_at_angle(&light, angle).red, 0f64);
+        }
+
+        // further along the axis the cone follows the inverse square law like a point light
+        assert!((light.get_light_color(Vector::from(0f64, 0f64, 2f64)).red - 0.5f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn spot_light_fades_smoothly_between_its_cones() {
+        let light = spot_light();
+        let falloffs: Vec<f64> = (1..20).map(|step| color_at_angle(&light, 0.2f64 + 0.2f64 * step as f64 / 20f64).red / 2f64).collect();
+
+        assert!(falloffs.windows(2).all(|pair| pair[1] < pair[0]), "{:?}", falloffs);
+        assert!(falloffs.iter().all(|falloff| (0f64..1f64).contains(falloff)));
+
+        // the smoothstep is symmetric around the middle of the cosine range
+        let cos_middle = 0.5f64 * (0.2f64.cos() + 0.4f64.cos());
+        assert!((light.get_cone_falloff(cos_middle) - 0.5f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn gobo_pixels_are_projected_onto_the_cone() {
+        let mut light = spot_light();
+        let mut gobo = Screen::from(2, 2, Color::BLACK);
+        gobo.paint_at(0, 0, Color::from(1f64, 0f64, 0f64, 1f64)).unwrap();
+        gobo.paint_at(1, 0, Color::from(0f64, 1f64, 0f64, 1f64)).unwrap();
+        gobo.paint_at(1, 1, Color::from(0f64, 0f64, 1f64, 1f64)).unwrap();
+        light.gobo = Some(gobo);
+
+        let seen_at = |u: f64, v: f64| light.get_light_color(light.basis.u * u + light.basis.v * v + light.basis.w);
+
+        // +u is the right of the gobo and +v its top row
+        let (top_left, top_right, bottom_right, bottom_left) = (seen_at(-0.05f64, 0.05f64), seen_at(0.05f64, 0.05f64), seen_at(0.05f64, -0.05f64), seen_at(-0.05f64, -0.05f64));
+        assert!(top_left.red > 0f64 && top_left.green == 0f64 && top_left.blue == 0f64);
+        assert!(top_right.green > 0f64 && top_right.red == 0f64 && top_right.blue == 0f64);
+        assert!(bottom_right.blue > 0f64 && bottom_right.red == 0f64 && bottom_right.green == 0f64);
+        assert_eq!(bottom_left.max_component(), 0f64);
+    }
+
+    #[test]
+    fn spot_light_rejects_invalid_cones() {
+        assert!(SpotLight::from(Vector::ZERO, Vector::from(0f64, 0f64, 1f64), 1f64, Color::WHITE, 0.4f64, 0.2f64).is_none());
+        assert!(SpotLight::from(Vector::ZERO, Vector::from(0f64, 0f64, 1f64), 1f64, Color::WHITE, 0.2f64, FRAC_PI_2).is_none());
+        assert!(SpotLight::from(Vector::ZERO, Vector::ZERO, 1f64, Color::WHITE, 0.2f64, 0.4f64).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use crate::basis::Basis;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::projection::{right_of, Projection};
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::render_settings::RenderSettings;
+use crate::scene::Scene;
+use crate::screen::Screen;
+use crate::vector::Vector;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StereoLayout {
+    SideBySide,
+    OverUnder,
+    Anaglyph
+}
+
+struct ShiftedProjection {
+    projection: Arc<dyn Projection>,
+    screen_shift: f64
+}
+
+impl Projection for ShiftedProjection {
+    fn generate_ray(&self, basis: &Basis, position: Vector, screen_x: f64, screen_y: f64, aspect_ratio: f64, random: &mut Random) -> Option<Ray> {
+        self.projection.generate_ray(basis, position, screen_x + self.screen_shift, screen_y, aspect_ratio, random)
+    }
+}
+
+pub struct StereoCamera {
+    pub left: Camera,
+    pub right: Camera,
+    pub layout: StereoLayout
+}
+
+impl StereoCamera {
+    pub fn from(camera: &Camera, interpupillary_distance: f64, convergence_distance: f64, layout: StereoLayout) -> Option<StereoCamera> {
+        if interpupillary_distance < 0f64 || convergence_distance <= 0f64 {
+            return None;
+        }
+
+        // offsets are measured to the right of the view, so the left eye gets the negative one
+        let eye_offset = interpupillary_distance / 2f64;
+
+        let left = StereoCamera::eye_camera(camera, -eye_offset, convergence_distance);
+        let right = StereoCamera::eye_camera(camera, eye_offset, convergence_distance);
+
+        Some(StereoCamera { left, right, layout })
+    }
+
+    // both eyes look along the camera's axis, turning them in would skew their images against each other,
+    // so they converge by shifting their images sideways instead
+    fn eye_camera(camera: &Camera, eye_offset: f64, convergence_distance: f64) -> Camera {
+        let screen_shift = camera.projection.get_screen_x_from_eye(eye_offset, convergence_distance);
+        let projection = Arc::new(ShiftedProjection { projection: camera.projection.clone(), screen_shift });
+
+        Camera { position: camera.position + right_of(&camera.basis) * eye_offset, projection, ..camera.clone() }
+    }
+
+    pub fn print_to_screen(&self, scene: &Scene, screen: &mut Screen, settings: &RenderSettings) {
+        let (width, height) = screen.resolution();
+
+        // both halves keep the full sensor, which gives the usual squeezed half resolution stereo frames
+        let (left_origin, left_size, right_origin, right_size) = match self.layout {
+            StereoLayout::SideBySide => ((0, 0), (width / 2, height), (width / 2, 0), (width - width / 2, height)),
+            StereoLayout::OverUnder => ((0, 0), (width, height / 2), (0, height / 2), (width, height - height / 2)),
+            StereoLayout::Anaglyph => ((0, 0), (width, height), (0, 0), (width, height))
+        };
+
+        let mut left_screen = Screen::from(left_size.0, left_size.1, Color::BLACK);
+        let mut right_screen = Screen::from(right_size.0, right_size.1, Color::BLACK);
+
+        self.left.print_to_screen(scene, &mut left_screen, settings);
+        self.right.print_to_screen(scene, &mut right_screen, settings);
+
+        match self.layout {
+            StereoLayout::Anaglyph => {
+                for (x, y) in screen.pixel_positions() {
+                    let left_color = left_screen.get_color_at(x, y).unwrap_or(Color::BLACK);
+                    let right_color = right_screen.get_color_at(x, y).unwrap_or(Color::BLACK);
+
+                    // red comes from the left eye, green and blue from the right one
+                    screen.paint_at(x, y, Color::from(left_color.red, right_color.green, right_color.blue, 1f64)).expect("should not go wrong");
+                }
+            },
+            _ => {
+                screen.paint_screen_at(left_origin.0, left_origin.1, &left_screen).expect("should not go wrong");
+                screen.paint_screen_at(right_origin.0, right_origin.1, &right_screen).expect("should not go wrong");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::from(Vector::from(1f64, 2f64, 3f64), Vector::from(0f64, 1f64, 0f64), Vector::from(1f64, 0f64, 1f64), 8f64, Vector::from(16f64, 9f64, 0f64)).unwrap()
+    }
+
+    #[test]
+    fn eyes_are_the_interpupillary_distance_apart_along_u() {
+        let camera = camera();
+        let stereo_camera = StereoCamera::from(&camera, 0.65f64, 30f64, StereoLayout::SideBySide).unwrap();
+        let offset = stereo_camera.left.position - stereo_camera.right.position;
+
+        assert!((offset.dot(camera.basis.u) - 0.65f64).abs() < 1e-12);
+        assert!(offset.dot(camera.basis.v).abs() < 1e-12 && offset.dot(camera.basis.w).abs() < 1e-12);
+        assert!((stereo_camera.left.position + stereo_camera.right.position - camera.position * 2f64).length() < 1e-12);
+    }
+
+    #[test]
+    fn eyes_stay_parallel_and_center_the_convergence_point() {
+        let camera = camera();
+        let stereo_camera = StereoCamera::from(&camera, 0.65f64, 30f64, StereoLayout::Anaglyph).unwrap();
+        let convergence_point = camera.position - camera.basis.w * 30f64;
+
+        for eye in [&stereo_camera.left, &stereo_camera.right] {
+            assert!((eye.basis.w - camera.basis.w).length() < 1e-12 && (eye.basis.u - camera.basis.u).length() < 1e-12);
+
+            let ray = eye.generate_ray(8f64, 4.5f64, 16, 9, &mut Random::from_seed(1)).unwrap();
+            let closest_point = ray.point_at((convergence_point - ray.starting_point).dot(ray.direction));
+            assert!((closest_point - convergence_point).length() < 1e-9, "{:?}", closest_point - convergence_point);
+        }
+    }
+}
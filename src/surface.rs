@@ -8,10 +8,11 @@ pub enum RayIntersectionResult {
     Intersected {
         surface_material: Material,
         surface_normal: Vector,
-        t: f64
+        t: f64,
+        front_face: bool
     }
 }
 
-pub trait Surface {
+pub trait Surface: Sync {
     fn intersect_with_ray(&self, ray: Ray) -> RayIntersectionResult;
 }
\ No newline at end of file
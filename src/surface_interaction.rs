@@ -0,0 +1,131 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+use crate::integrator::SURFACE_OFFSET;
+use crate::material::Material;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vector::Vector;
+
+// the fractions of light a material sends into each lobe, scaled down together when they add up to more than one
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LobeWeights {
+    pub diffuse: f64,
+    pub specular: f64,
+    pub mirror: f64,
+    pub transmission: f64
+}
+
+impl LobeWeights {
+    pub fn of(material: &Material) -> LobeWeights {
+        let weight_sum = material.diffuse_coefficient + material.specular_coefficient + material.mirror_reflection_coefficient + material.transmission_coefficient;
+        let energy_scale = if weight_sum > 1f64 { 1f64 / weight_sum } else { 1f64 };
+
+        LobeWeights {
+            diffuse: material.diffuse_coefficient * energy_scale,
+            specular: material.specular_coefficient * energy_scale,
+            mirror: material.mirror_reflection_coefficient * energy_scale,
+            transmission: material.transmission_coefficient * energy_scale
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.diffuse + self.specular + self.mirror + self.transmission
+    }
+}
+
+// a hit shaded by the path tracer and the photon mapper, with the normal turned towards the side the ray came from
+pub struct SurfaceInteraction {
+    pub material: Material,
+    pub weights: LobeWeights,
+    pub position: Vector,
+    pub normal: Vector,
+    pub front_face: bool,
+    // new rays leave from just above the surface so they cannot hit it again right away
+    pub origin: Vector,
+    ray: Ray,
+    t: f64
+}
+
+impl SurfaceInteraction {
+    pub fn from(ray: Ray, material: Material, surface_normal: Vector, t: f64, front_face: bool) -> SurfaceInteraction {
+        let normal = if surface_normal.dot(ray.direction) > 0f64 { -surface_normal } else { surface_normal };
+        let position = ray.point_at(t);
+
+        SurfaceInteraction { material, weights: LobeWeights::of(&material), position, normal, front_face, origin: position + normal * SURFACE_OFFSET, ray, t }
+    }
+
+    // what the media and, after a back face hit, the material the ray travelled through let through on the way here
+    pub fn get_transmittance_to(&self, scene: &Scene, random: &mut Random) -> Color {
+        let transmittance = if self.front_face { Color::WHITE } else { self.material.transmittance_over(self.t) };
+
+        if scene.media.is_empty() {
+            return transmittance;
+        }
+
+        transmittance * scene.get_medium_transmittance(self.ray, self.t, random)
+    }
+
+    // light scattered towards the ray origin by the media on the way here
+    pub fn get_medium_radiance_to(&self, scene: &Scene, random: &mut Random) -> Color {
+        if scene.media.is_empty() {
+            return Color::BLACK;
+        }
+
+        scene.get_medium_radiance(self.ray, self.t, random)
+    }
+
+    pub fn evaluate_brdf(&self, incoming: Vector) -> Color {
+        let bisector = (incoming - self.ray.direction).normalized_or_zero();
+        let specular = self.weights.specular * (self.material.shininess_coefficient + 8f64) / (8f64 * PI) * bisector.dot(self.normal).max(0f64).powf(self.material.shininess_coefficient);
+
+        self.material.color * (self.weights.diffuse / PI + specular)
+    }
+
+    // light coming through transparent surfaces is found by refracted paths or caustic photons, so shadow rays stay binary here
+    pub fn sample_direct_light(&self, scene: &Scene, random: &mut Random) -> Color {
+        let mut radiance = Color::BLACK;
+
+        for (light_source, light_weight) in scene.select_light_sources(self.origin, random) {
+            let shadow_sample_count = light_source.get_shadow_sample_count();
+
+            for _ in 0..shadow_sample_count {
+                let light_sample = light_source.sample_from(self.origin, random);
+                let direction_to_light = -light_sample.direction;
+                let cosine_to_light = self.normal.dot(direction_to_light);
+
+                if cosine_to_light <= 0f64 {
+                    continue;
+                }
+
+                if let Some(shadow_ray) = self.ray.spawn(self.origin, direction_to_light) {
+                    if scene.is_unoccluded(shadow_ray, light_sample.distance) {
+                        let medium_transmittance = scene.get_medium_transmittance(shadow_ray, light_sample.distance, random);
+                        radiance = radiance + self.evaluate_brdf(direction_to_light) * light_sample.color * medium_transmittance * (light_weight * cosine_to_light / shadow_sample_count as f64);
+                    }
+                }
+            }
+        }
+
+        radiance
+    }
+
+    pub fn reflect(&self) -> (Vector, Vector) {
+        (self.origin, self.ray.direction.reflected(self.normal))
+    }
+
+    // reflection and refraction are picked with the fresnel weights, so those cancel out and only the tint of refracted light is returned
+    pub fn sample_dielectric(&self, random: &mut Random) -> (Vector, Vector, Color) {
+        let relative_refractive_index = if self.front_face { 1f64 / self.material.refractive_index } else { self.material.refractive_index };
+        let reflectance = self.material.fresnel_model.reflectance(-self.ray.direction.dot(self.normal), relative_refractive_index);
+
+        match self.ray.direction.refracted(self.normal, relative_refractive_index) {
+            Some(direction) if random.next_f64() >= reflectance => (self.origin - self.normal * (2f64 * SURFACE_OFFSET), direction, self.material.color),
+            _ => {
+                let (origin, direction) = self.reflect();
+                (origin, direction, Color::WHITE)
+            }
+        }
+    }
+}
@@ -5,7 +5,7 @@ use crate::vector_base;
 vector_base!(Vector<f64> {x,y,z});
 
 impl Vector {
-    pub(crate) const ZERO: Vector = Vector { x: 0f64, y: 0f64, z: 0f64 };
+    pub const ZERO: Vector = Vector { x: 0f64, y: 0f64, z: 0f64 };
     pub fn from(x: f64, y: f64, z: f64) -> Vector {
         Vector{ x, y, z }
     }
@@ -25,6 +25,17 @@ impl Vector {
         self - 2f64 * self.dot(axis) * axis
     }
 
+    pub fn refracted(self, normal: Vector, relative_refractive_index: f64) -> Option<Vector> {
+        let cosine_incident = -self.dot(normal);
+        let sine_transmitted_squared = relative_refractive_index.powi(2) * (1f64 - cosine_incident.powi(2));
+
+        if sine_transmitted_squared > 1f64 {
+            return None;
+        }
+
+        Some(self * relative_refractive_index + normal * (relative_refractive_index * cosine_incident - (1f64 - sine_transmitted_squared).sqrt()))
+    }
+
     pub fn rotated(self, angle: f64, axis: Vector) -> Vector {
         let axis = axis.normalized_or_zero();
         let sin_theta = angle.sin();
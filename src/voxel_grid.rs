@@ -0,0 +1,143 @@
+use std::fs;
+use std::io;
+use std::io::{Error, ErrorKind};
+
+use crate::vector::Vector;
+
+pub struct VoxelGrid {
+    values: Vec<f64>,
+    width: usize,
+    height: usize,
+    depth: usize,
+    max_value: f64
+}
+
+impl VoxelGrid {
+    // values are stored with x changing fastest, then y, then z
+    pub fn from(width: usize, height: usize, depth: usize, values: Vec<f64>) -> Option<VoxelGrid> {
+        if width == 0 || height == 0 || depth == 0 || values.len() != width * height * depth || values.iter().any(|value| !value.is_finite() || *value < 0f64) {
+            return None;
+        }
+
+        let max_value = values.iter().fold(0f64, |max_value, value| max_value.max(*value));
+        Some(VoxelGrid { values, width, height, depth, max_value })
+    }
+
+    pub fn read_raw(path: &str) -> io::Result<VoxelGrid> {
+        VoxelGrid::from_raw_bytes(&fs::read(path)?)
+    }
+
+    // three little endian u32 dimensions followed by one little endian f32 per voxel
+    pub fn from_raw_bytes(bytes: &[u8]) -> io::Result<VoxelGrid> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        if bytes.len() < 12 {
+            return Err(invalid("incomplete header"));
+        }
+
+        let dimensions: Vec<usize> = bytes[..12].chunks_exact(4).map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]) as usize).collect();
+        let voxel_count = dimensions[0].checked_mul(dimensions[1]).and_then(|count| count.checked_mul(dimensions[2])).ok_or_else(|| invalid("grid too large"))?;
+
+        if bytes.len() - 12 < voxel_count.saturating_mul(4) {
+            return Err(invalid("not enough voxel values"));
+        }
+
+        let values = bytes[12..].chunks_exact(4).take(voxel_count).map(|word| f32::from_le_bytes([word[0], word[1], word[2], word[3]]) as f64).collect();
+
+        VoxelGrid::from(dimensions[0], dimensions[1], dimensions[2], values).ok_or_else(|| invalid("invalid grid"))
+    }
+
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    fn value_at(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.values[(z * self.height + y) * self.width + x]
+    }
+
+    // trilinear interpolation between voxel centers, the position is in grid space where the grid spans 0 to 1 on every axis
+    pub fn sample(&self, position: Vector) -> f64 {
+        if position.x < 0f64 || position.y < 0f64 || position.z < 0f64 || position.x > 1f64 || position.y > 1f64 || position.z > 1f64 {
+            return 0f64;
+        }
+
+        let axis = |coordinate: f64, size: usize| {
+            let continuous = (coordinate * size as f64 - 0.5f64).clamp(0f64, (size - 1) as f64);
+            let lower = (continuous.floor() as usize).min(size - 1);
+            (lower, (lower + 1).min(size - 1), continuous - lower as f64)
+        };
+
+        let (x0, x1, fx) = axis(position.x, self.width);
+        let (y0, y1, fy) = axis(position.y, self.height);
+        let (z0, z1, fz) = axis(position.z, self.depth);
+
+        let lerp = |a: f64, b: f64, fraction: f64| a + (b - a) * fraction;
+        let along_x = |y: usize, z: usize| lerp(self.value_at(x0, y, z), self.value_at(x1, y, z), fx);
+
+        lerp(lerp(along_x(y0, z0), along_x(y1, z0), fy), lerp(along_x(y0, z1), along_x(y1, z1), fy), fz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_bytes(dimensions: [u32; 3], values: &[f32]) -> Vec<u8> {
+        dimensions.iter().flat_map(|dimension| dimension.to_le_bytes())
+            .chain(values.iter().flat_map(|value| value.to_le_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn raw_grid_is_read_with_x_changing_fastest() {
+        let grid = VoxelGrid::from_raw_bytes(&raw_bytes([2, 1, 2], &[1f32, 2f32, 3f32, 4f32])).unwrap();
+
+        assert_eq!((grid.width, grid.height, grid.depth), (2, 1, 2));
+        assert_eq!(grid.value_at(1, 0, 0), 2f64);
+        assert_eq!(grid.value_at(0, 0, 1), 3f64);
+        assert_eq!(grid.max_value(), 4f64);
+    }
+
+    #[test]
+    fn short_header_is_rejected() {
+        assert!(VoxelGrid::from_raw_bytes(&[1, 0, 0, 0, 1, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn truncated_values_are_rejected() {
+        assert!(VoxelGrid::from_raw_bytes(&raw_bytes([2, 2, 2], &[1f32; 7])).is_err());
+    }
+
+    #[test]
+    fn zero_dimensions_are_rejected() {
+        assert!(VoxelGrid::from_raw_bytes(&raw_bytes([0, 4, 4], &[])).is_err());
+    }
+
+    #[test]
+    fn overflowing_dimensions_are_rejected() {
+        assert!(VoxelGrid::from_raw_bytes(&raw_bytes([u32::MAX, u32::MAX, u32::MAX], &[1f32])).is_err());
+    }
+
+    #[test]
+    fn negative_densities_are_rejected() {
+        assert!(VoxelGrid::from_raw_bytes(&raw_bytes([1, 1, 1], &[-1f32])).is_err());
+    }
+
+    #[test]
+    fn sampling_hits_voxel_values_at_their_centers_and_blends_between_them() {
+        let grid = VoxelGrid::from(2, 2, 2, vec![0f64, 1f64, 2f64, 3f64, 4f64, 5f64, 6f64, 7f64]).unwrap();
+
+        for z in 0..2 {
+            for y in 0..2 {
+                for x in 0..2 {
+                    let center = Vector::from((x as f64 + 0.5f64) / 2f64, (y as f64 + 0.5f64) / 2f64, (z as f64 + 0.5f64) / 2f64);
+                    assert!((grid.sample(center) - grid.value_at(x, y, z)).abs() < 1e-12);
+                }
+            }
+        }
+
+        assert!((grid.sample(Vector::from(0.5f64, 0.5f64, 0.5f64)) - 3.5f64).abs() < 1e-12);
+        assert!((grid.sample(Vector::from(0.5f64, 0.25f64, 0.25f64)) - 0.5f64).abs() < 1e-12);
+        assert_eq!(grid.sample(Vector::from(1.5f64, 0.5f64, 0.5f64)), 0f64);
+    }
+}
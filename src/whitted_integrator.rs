@@ -0,0 +1,174 @@
+use crate::ambient_occlusion::AmbientOcclusion;
+use crate::color::Color;
+use crate::integrator::{Integrator, SURFACE_OFFSET};
+use crate::light_ray::LightRay;
+use crate::material::Material;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::surface::RayIntersectionResult;
+use crate::vector::Vector;
+
+pub struct WhittedIntegrator {
+    max_depth: u8,
+    ambient_occlusion: Option<AmbientOcclusion>,
+    ambient_color: Color
+}
+
+impl WhittedIntegrator {
+    pub fn from(max_depth: u8) -> WhittedIntegrator {
+        WhittedIntegrator { max_depth, ambient_occlusion: None, ambient_color: Color::ZERO }
+    }
+
+    // adds an ambient term to diffuse surfaces at primary hits, darkened where nearby geometry blocks it
+    pub fn with_ambient_occlusion(max_depth: u8, ambient_occlusion: AmbientOcclusion, ambient_color: Color) -> WhittedIntegrator {
+        WhittedIntegrator { max_depth, ambient_occlusion: Some(ambient_occlusion), ambient_color }
+    }
+
+    fn get_final_color_from_ray(&self, ray: Ray, scene: &Scene, reflections: u8, random: &mut Random) -> Color {
+        match self.get_final_ray(ray, scene, reflections, random) {
+            Some(ray) => ray.color,
+            _ => Color::BLACK
+        }
+    }
+
+    fn get_final_ray(&self, ray: Ray, scene: &Scene, reflections: u8, random: &mut Random) -> Option<LightRay> {
+        if reflections == 0 {
+            return None;
+        }
+
+        let surface = scene.surface;
+        let ray_intersection = surface.intersect_with_ray(ray);
+
+        match ray_intersection {
+            RayIntersectionResult::Intersected { surface_material, surface_normal, t, front_face } => {
+                let reflection_point = ray.point_at(t);
+                let direction_from_camera = ray.direction;
+
+                // new rays leave from just above the side of the surface the ray came from, so they cannot hit it again right away
+                let facing_normal = if surface_normal.dot(direction_from_camera) > 0f64 { -surface_normal } else { surface_normal };
+                let offset_point = reflection_point + facing_normal * SURFACE_OFFSET;
+                let reflection_ray = ray.spawn(offset_point, direction_from_camera.reflected(surface_normal)).unwrap();
+
+                // every light gets its own shadow rays and adds its own share of the received color
+                let mut received_color = Color::ZERO;
+
+                for (light_source, light_weight) in scene.select_light_sources(reflection_point, random) {
+                    let shadow_sample_count = light_source.get_shadow_sample_count();
+
+                    for _ in 0..shadow_sample_count {
+                        let light_sample = light_source.sample_from(reflection_point, random);
+                        let ray_to_light = match ray.spawn(offset_point, -light_sample.direction) {
+                            Some(ray_to_light) => ray_to_light,
+                            None => continue
+                        };
+
+                        let light_transmittance = scene.get_shadow_transmittance(ray_to_light, light_sample.distance) * scene.get_medium_transmittance(ray_to_light, light_sample.distance, random);
+
+                        let diffuse_multiplier = surface_material.diffuse_coefficient * surface_normal.dot(ray_to_light.direction).max(0f64);
+
+                        let bisector = (-direction_from_camera + ray_to_light.direction).normalized_or_zero();
+
+                        let specular_multiplier = surface_material.specular_coefficient * bisector.dot(surface_normal).max(0f64).powf(surface_material.shininess_coefficient);
+
+                        let sample_weight = light_weight * (diffuse_multiplier + specular_multiplier) / shadow_sample_count as f64;
+                        received_color = received_color + light_sample.color * light_transmittance * sample_weight;
+                    }
+                }
+
+                let reflection_color = match self.get_final_ray(reflection_ray, scene, reflections - 1, random) {
+                    Some(light_ray) => light_ray.color * surface_material.mirror_reflection_coefficient,
+                    _ => Color::ZERO
+                };
+
+                // the reflection is only weighted by the mirror coefficient, so it does not change with the lights in the scene
+                let surface_color = Color::mix_of(vec![surface_material.color, received_color + reflection_color]);
+
+                let surface_color = match self.ambient_occlusion {
+                    Some(ambient_occlusion) if reflections == self.max_depth && surface_material.diffuse_coefficient > 0f64 => {
+                        let unoccluded_fraction = ambient_occlusion.get_unoccluded_fraction(ray, reflection_point, facing_normal, surface, random);
+
+                        surface_color + surface_material.color * self.ambient_color * (surface_material.diffuse_coefficient * unoccluded_fraction)
+                    },
+                    _ => surface_color
+                };
+                let transmission = surface_material.transmission_coefficient;
+
+                let color = if transmission > 0f64 {
+                    surface_color * (1f64 - transmission) + self.get_dielectric_color(ray, reflection_point, surface_normal, front_face, &surface_material, scene, reflections, random) * transmission
+                } else {
+                    surface_color
+                };
+
+                // a back face hit means the ray travelled through the material to get here
+                let color = if front_face { color + surface_material.emitted_radiance() } else { color * surface_material.transmittance_over(t) };
+
+                LightRay::from(reflection_point, -ray.direction, scene.apply_media(ray, t, color, random))
+            },
+
+            RayIntersectionResult::NoIntersection => {
+                let background_color = scene.apply_media(ray, f64::INFINITY, scene.get_background_color(ray.direction), random);
+                Some(LightRay::from_ray(ray, background_color))
+            }
+        }
+    }
+}
+
+impl WhittedIntegrator {
+    #[allow(clippy::too_many_arguments)]
+    fn get_dielectric_color(&self, ray: Ray, point: Vector, surface_normal: Vector, front_face: bool, material: &Material, scene: &Scene, reflections: u8, random: &mut Random) -> Color {
+        let normal = if front_face { surface_normal } else { -surface_normal };
+        let relative_refractive_index = if front_face { 1f64 / material.refractive_index } else { material.refractive_index };
+        let reflectance = material.fresnel_model.reflectance(-ray.direction.dot(normal), relative_refractive_index);
+
+        let reflected_color = match ray.spawn(point + normal * SURFACE_OFFSET, ray.direction.reflected(normal)) {
+            Some(reflected_ray) => self.get_final_color_from_ray(reflected_ray, scene, reflections - 1, random),
+            None => Color::ZERO
+        };
+
+        let refracted_color = match ray.direction.refracted(normal, relative_refractive_index).and_then(|direction| ray.spawn(point - normal * SURFACE_OFFSET, direction)) {
+            Some(refracted_ray) => self.get_final_color_from_ray(refracted_ray, scene, reflections - 1, random),
+            None => Color::ZERO
+        };
+
+        reflected_color * reflectance + material.color * refracted_color * (1f64 - reflectance)
+    }
+}
+
+impl Integrator for WhittedIntegrator {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, random: &mut Random) -> Color {
+        self.get_final_color_from_ray(ray, scene, self.max_depth, random)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directional_light::DirectionalLight;
+    use crate::infinite_plane::InfinitePlane;
+    use crate::light_source::LightSource;
+    use crate::multisurface::MultiSurface;
+
+    fn mirror_radiance(light_sources: Vec<&dyn LightSource>) -> Color {
+        let mirror = InfinitePlane::from(Vector::ZERO, Vector::from(0f64, 1f64, 0f64), Material::from(Color::WHITE, 0f64, 0f64, 1f64, 0.8f64)).unwrap();
+        let ceiling = InfinitePlane::from(Vector::from(0f64, 2f64, 0f64), Vector::from(0f64, -1f64, 0f64), Material::emissive(Color::WHITE, 1f64).unwrap()).unwrap();
+        let world = MultiSurface::from(vec![&mirror, &ceiling]);
+        let scene = Scene::from(&world, light_sources);
+
+        let ray = Ray::from(Vector::from(0f64, 1f64, 0f64), Vector::from(1f64, -1f64, 0f64)).unwrap();
+        WhittedIntegrator::from(3).get_radiance(ray, &scene, &mut Random::from_seed(1))
+    }
+
+    #[test]
+    fn mirror_reflections_are_weighted_by_the_mirror_coefficient_alone() {
+        let first_light = DirectionalLight::from(Vector::from(0f64, -1f64, 0f64), Color::WHITE).unwrap();
+        let second_light = DirectionalLight::from(Vector::from(1f64, 1f64, 0f64), Color::WHITE).unwrap();
+
+        // the mirror has no diffuse or specular lobe, so all of its color is the reflected glow of the ceiling
+
+        for light_sources in [Vec::new(), vec![&first_light as &dyn LightSource], vec![&first_light, &second_light]] {
+            let radiance = mirror_radiance(light_sources);
+            assert!((radiance.red - 0.8f64).abs() < 1e-9, "{}", radiance.red);
+        }
+    }
+}